@@ -0,0 +1,283 @@
+use anyhow::Result;
+use base64::Engine;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+use std::fmt;
+use tokio::sync::Mutex;
+
+const AUTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+// Refresh a little before the token actually expires so in-flight requests don't race it
+const REFRESH_MARGIN_MINUTES: i64 = 5;
+// Used when a token's `exp` claim can't be read
+const FALLBACK_TOKEN_LIFETIME_MINUTES: i64 = 30;
+
+/// A password, token or key from the config file. Never printed, not even in
+/// `Debug` output, so it can't end up in the logs by accident.
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+/// How requests to a PocketBase source are authenticated (`[auth]` in the config).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum AuthConfig {
+    /// A fixed token sent as `Authorization: Bearer <token>`
+    Bearer { token: Secret },
+    /// A fixed key sent in a custom header
+    ApiKey {
+        #[serde(default = "default_api_key_header")]
+        header: String,
+        key: Secret,
+    },
+    /// Password login against an auth collection. `collection = "admins"` uses
+    /// the pre-0.23 admin endpoint; newer servers use `_superusers` instead.
+    Pocketbase {
+        identity: String,
+        password: Secret,
+        #[serde(default = "default_auth_collection")]
+        collection: String,
+    },
+}
+
+fn default_api_key_header() -> String {
+    String::from("X-API-Key")
+}
+
+fn default_auth_collection() -> String {
+    String::from("users")
+}
+
+struct SessionToken {
+    token: Secret,
+    expires_at: DateTime<Utc>,
+}
+
+/// Adds credentials to outgoing requests, logging in and refreshing
+/// PocketBase session tokens as needed.
+pub struct Authenticator {
+    config: AuthConfig,
+    base_url: String,
+    client: reqwest::Client,
+    session: Mutex<Option<SessionToken>>,
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("config", &self.config)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize)]
+struct AuthResponse {
+    token: String,
+}
+
+impl Authenticator {
+    pub fn new(config: AuthConfig, base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(AUTH_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            config,
+            base_url,
+            client,
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Returns `request` with the configured credentials attached.
+    pub async fn authorize(&self, request: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
+        match &self.config {
+            AuthConfig::Bearer { token } => Ok(request.bearer_auth(token.expose())),
+            AuthConfig::ApiKey { header, key } => Ok(request.header(header.as_str(), key.expose())),
+            AuthConfig::Pocketbase { .. } => {
+                let token = self.session_token().await?;
+                Ok(request.header("Authorization", token.expose()))
+            }
+        }
+    }
+
+    /// Drops the cached session after the server rejected it, so the next
+    /// `authorize` logs in again. Returns false when retrying can't help.
+    pub async fn invalidate(&self) -> bool {
+        match self.config {
+            AuthConfig::Pocketbase { .. } => {
+                tracing::warn!("Session token rejected, will log in again");
+                *self.session.lock().await = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    async fn session_token(&self) -> Result<Secret> {
+        let mut session = self.session.lock().await;
+        let refresh_at = Utc::now() + Duration::minutes(REFRESH_MARGIN_MINUTES);
+
+        if let Some(current) = session.as_ref() {
+            if current.expires_at > refresh_at {
+                return Ok(current.token.clone());
+            }
+
+            tracing::info!("Session token expires at {}, refreshing", current.expires_at);
+            match self.refresh(&current.token).await {
+                Ok(refreshed) => {
+                    let token = refreshed.token.clone();
+                    *session = Some(refreshed);
+                    return Ok(token);
+                }
+                Err(e) => tracing::warn!("Token refresh failed, logging in again: {}", e),
+            }
+        }
+
+        let fresh = self.login().await?;
+        let token = fresh.token.clone();
+        *session = Some(fresh);
+        Ok(token)
+    }
+
+    async fn login(&self) -> Result<SessionToken> {
+        let AuthConfig::Pocketbase { identity, password, collection } = &self.config else {
+            return Err(anyhow::anyhow!("Login is only supported for PocketBase auth"));
+        };
+
+        let url = if collection == "admins" {
+            format!("{}/api/admins/auth-with-password", self.base_url)
+        } else {
+            format!("{}/api/collections/{}/auth-with-password", self.base_url, collection)
+        };
+        tracing::info!("Logging in to {} as {}", url, identity);
+
+        let response: AuthResponse = self.client.post(&url)
+            .json(&serde_json::json!({
+                "identity": identity,
+                "password": password.expose(),
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(SessionToken::new(response.token))
+    }
+
+    async fn refresh(&self, current: &Secret) -> Result<SessionToken> {
+        let AuthConfig::Pocketbase { collection, .. } = &self.config else {
+            return Err(anyhow::anyhow!("Refresh is only supported for PocketBase auth"));
+        };
+
+        let url = if collection == "admins" {
+            format!("{}/api/admins/auth-refresh", self.base_url)
+        } else {
+            format!("{}/api/collections/{}/auth-refresh", self.base_url, collection)
+        };
+
+        let response: AuthResponse = self.client.post(&url)
+            .header("Authorization", current.expose())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(SessionToken::new(response.token))
+    }
+}
+
+impl SessionToken {
+    fn new(token: String) -> Self {
+        let expires_at = token_expiry(&token).unwrap_or_else(|| {
+            tracing::warn!("Could not read token expiry, assuming {} minutes", FALLBACK_TOKEN_LIFETIME_MINUTES);
+            Utc::now() + Duration::minutes(FALLBACK_TOKEN_LIFETIME_MINUTES)
+        });
+        tracing::info!("Session token valid until {}", expires_at);
+
+        Self { token: Secret(token), expires_at }
+    }
+}
+
+/// Reads the `exp` claim from a JWT without verifying it; the server does that.
+fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    struct Claims {
+        exp: i64,
+    }
+
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: Claims = serde_json::from_slice(&bytes).ok()?;
+    Utc.timestamp_opt(claims.exp, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        format!("{}.{}.signature", engine.encode(r#"{"alg":"HS256"}"#), engine.encode(claims))
+    }
+
+    fn authorized(config: AuthConfig) -> reqwest::Request {
+        let auth = Authenticator::new(config, String::from("http://sign.example"));
+        let request = reqwest::Client::new().get("http://sign.example/api/events");
+        futures::executor::block_on(auth.authorize(request)).unwrap().build().unwrap()
+    }
+
+    #[test]
+    fn expiry_is_read_from_the_token() {
+        let expiry = token_expiry(&jwt(r#"{"id":"abc","exp":1767225600}"#));
+        assert_eq!(expiry, Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).single());
+    }
+
+    #[test]
+    fn unreadable_tokens_have_no_expiry() {
+        assert_eq!(token_expiry("not-a-jwt"), None);
+        assert_eq!(token_expiry("a.!!!.c"), None);
+        assert_eq!(token_expiry(&jwt(r#"{"id":"abc"}"#)), None);
+    }
+
+    #[test]
+    fn secrets_are_not_printed() {
+        let config: AuthConfig = toml::from_str("method = \"bearer\"\ntoken = \"hunter2\"").unwrap();
+        assert!(!format!("{:?}", config).contains("hunter2"));
+    }
+
+    #[test]
+    fn fixed_credentials_are_attached() {
+        let bearer: AuthConfig = toml::from_str("method = \"bearer\"\ntoken = \"t0k\"").unwrap();
+        let request = authorized(bearer);
+        assert_eq!(request.headers()["Authorization"], "Bearer t0k");
+
+        let api_key: AuthConfig = toml::from_str("method = \"api_key\"\nkey = \"k3y\"").unwrap();
+        let request = authorized(api_key);
+        assert_eq!(request.headers()["X-API-Key"], "k3y");
+    }
+
+    #[test]
+    fn login_collection_defaults_to_users() {
+        let config: AuthConfig =
+            toml::from_str("method = \"pocketbase\"\nidentity = \"sign\"\npassword = \"pw\"").unwrap();
+        assert!(matches!(config, AuthConfig::Pocketbase { collection, .. } if collection == "users"));
+    }
+}
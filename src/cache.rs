@@ -0,0 +1,141 @@
+use crate::pocketbase::ApiEvent;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Bump whenever the on-disk layout or `ApiEvent` changes incompatibly
+const CACHE_VERSION: u32 = 1;
+
+/// Last successfully fetched event list, kept on disk so the sign has
+/// something to show when the API is unreachable.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventCache {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub events: Vec<ApiEvent>,
+}
+
+impl EventCache {
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::cache_path()?)
+    }
+
+    pub fn save(events: &[ApiEvent]) -> Result<()> {
+        Self::save_to(&Self::cache_path()?, events)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let cache: EventCache = serde_json::from_str(&contents)?;
+
+        if cache.version != CACHE_VERSION {
+            return Err(anyhow::anyhow!(
+                "Cache version {} does not match expected version {}",
+                cache.version,
+                CACHE_VERSION
+            ));
+        }
+
+        tracing::info!(
+            "Loaded {} cached events saved at {} from {}",
+            cache.events.len(),
+            cache.saved_at,
+            path.display()
+        );
+        Ok(cache)
+    }
+
+    fn save_to(path: &Path, events: &[ApiEvent]) -> Result<()> {
+        let cache = EventCache {
+            version: CACHE_VERSION,
+            saved_at: Utc::now(),
+            events: events.to_vec(),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first so a crash mid-write never leaves a truncated cache
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&cache)?)?;
+        fs::rename(&tmp_path, path)?;
+
+        tracing::info!("Saved {} events to cache at {}", events.len(), path.display());
+        Ok(())
+    }
+
+    /// Where downloaded images are kept between requests, so a 304 can be
+    /// answered without holding a second copy of every image in memory.
+    pub fn image_dir() -> PathBuf {
+        let mut path = dirs::cache_dir().unwrap_or_else(std::env::temp_dir);
+        path.push("digital-sign");
+        path.push("images");
+        path
+    }
+
+    fn cache_path() -> Result<PathBuf> {
+        let mut path = dirs::cache_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find cache directory"))?;
+        path.push("digital-sign");
+        path.push("events.json");
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str) -> ApiEvent {
+        let start = Utc::now();
+        ApiEvent {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: None,
+            thumbnail: None,
+            category: String::new(),
+            is_featured: false,
+            recurring_type: None,
+            created_at: start,
+            updated_at: start,
+        }
+    }
+
+    fn cache_file(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("beacon-cache-test-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn saved_events_load_again() {
+        let path = cache_file("roundtrip/events.json");
+        EventCache::save_to(&path, &[event("a"), event("b")]).unwrap();
+
+        let cache = EventCache::load_from(&path).unwrap();
+        let ids: Vec<_> = cache.events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn other_version_is_rejected() {
+        let path = cache_file("version/events.json");
+        EventCache::save_to(&path, &[event("a")]).unwrap();
+        let stale = fs::read_to_string(&path)
+            .unwrap()
+            .replace(&format!("\"version\":{}", CACHE_VERSION), "\"version\":0");
+        fs::write(&path, stale).unwrap();
+
+        assert!(EventCache::load_from(&path).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::Result;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Validators of the last full response for one URL, and its body unless
+/// that is kept on disk.
+#[derive(Debug)]
+struct Entry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+/// Remembers `ETag`/`Last-Modified` per URL so repeat requests can be made
+/// conditional, and serves the stored body when the server answers 304.
+#[derive(Debug, Default)]
+pub struct ConditionalCache {
+    entries: Mutex<HashMap<String, Entry>>,
+    // Bodies are written here instead of kept in memory, if set
+    body_dir: Option<PathBuf>,
+    // Larger responses are rejected before they're stored
+    max_body_size: Option<u64>,
+}
+
+impl ConditionalCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps bodies as files in `dir` rather than in memory, for large
+    /// responses such as images. Files left over from an earlier run are removed.
+    pub fn on_disk(dir: PathBuf) -> Self {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to clear {}: {}", dir.display(), e);
+            }
+        }
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Failed to create {}, requests won't be conditional: {}", dir.display(), e);
+        }
+
+        Self {
+            entries: Mutex::default(),
+            body_dir: Some(dir),
+            max_body_size: None,
+        }
+    }
+
+    /// Rejects full responses larger than `bytes` with an error, without
+    /// storing them.
+    pub fn with_max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Adds `If-None-Match`/`If-Modified-Since` if we have validators for `url`.
+    pub fn prepare(&self, url: &str, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get(url) else {
+            return request;
+        };
+
+        let mut request = request;
+        if let Some(etag) = &entry.etag {
+            request = request.header(IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+        request
+    }
+
+    /// Returns the body for `url` and whether it changed since the last request.
+    /// A 304 yields the stored body; a full response replaces it, unless it's
+    /// over the size limit, which also drops what was stored before.
+    pub async fn body(&self, url: &str, response: reqwest::Response) -> Result<(Vec<u8>, bool)> {
+        if response.status() == StatusCode::NOT_MODIFIED {
+            let body = self.stored_body(url).await
+                .ok_or_else(|| anyhow::anyhow!("Got 304 for {} without a cached copy", url))?;
+            tracing::info!("Not modified, reusing {} cached bytes for {}", body.len(), url);
+            return Ok((body, false));
+        }
+
+        let cacheable = response.status().is_success();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        // Check the announced length first so an oversized body isn't downloaded
+        if let Some(length) = response.content_length() {
+            self.check_size(url, length)?;
+        }
+        let body = response.bytes().await?.to_vec();
+        self.check_size(url, body.len() as u64)?;
+
+        // Without validators a later request could never be answered with 304
+        if cacheable && (etag.is_some() || last_modified.is_some()) {
+            let stored = match &self.body_dir {
+                None => Some(body.clone()),
+                Some(dir) => {
+                    if let Err(e) = tokio::fs::write(body_path(dir, url), &body).await {
+                        tracing::warn!("Failed to store body of {}: {}", url, e);
+                        return Ok((body, true));
+                    }
+                    None
+                }
+            };
+            self.entries.lock().unwrap().insert(
+                url.to_string(),
+                Entry { etag, last_modified, body: stored },
+            );
+        }
+
+        Ok((body, true))
+    }
+
+    fn check_size(&self, url: &str, length: u64) -> Result<()> {
+        match self.max_body_size {
+            Some(max) if length > max => {
+                self.retain(|stored| stored != url);
+                Err(anyhow::anyhow!("Response of {} KB for {} is over the {} KB limit", length / 1024, url, max / 1024))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn stored_body(&self, url: &str) -> Option<Vec<u8>> {
+        if let Some(body) = &self.entries.lock().unwrap().get(url)?.body {
+            return Some(body.clone());
+        }
+
+        let path = body_path(self.body_dir.as_ref()?, url);
+        match tokio::fs::read(&path).await {
+            Ok(body) => Some(body),
+            Err(e) => {
+                tracing::warn!("Failed to read stored body {}: {}", path.display(), e);
+                self.entries.lock().unwrap().remove(url);
+                None
+            }
+        }
+    }
+
+    /// Forgets validators for URLs that are no longer in use.
+    pub fn retain(&self, keep: impl Fn(&str) -> bool) {
+        self.entries.lock().unwrap().retain(|url, _| {
+            if keep(url) {
+                return true;
+            }
+            if let Some(dir) = &self.body_dir {
+                let _ = std::fs::remove_file(body_path(dir, url));
+            }
+            false
+        });
+    }
+}
+
+/// File holding the body of `url`. Only used within one run, so the hash
+/// doesn't need to be stable across builds.
+fn body_path(dir: &Path, url: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    dir.join(format!("{:016x}", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    const URL: &str = "http://sign.example/api/events";
+
+    fn response(status: u16, etag: Option<&str>, body: &str) -> reqwest::Response {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(etag) = etag {
+            builder = builder.header(ETAG, etag);
+        }
+        builder.body(body.to_string()).unwrap().into()
+    }
+
+    fn sent_headers(cache: &ConditionalCache) -> reqwest::header::HeaderMap {
+        let request = cache.prepare(URL, reqwest::Client::new().get(URL));
+        request.build().unwrap().headers().clone()
+    }
+
+    #[test]
+    fn not_modified_reuses_stored_body() {
+        let cache = ConditionalCache::new();
+
+        let (body, changed) = block_on(cache.body(URL, response(200, Some("\"v1\""), "events"))).unwrap();
+        assert_eq!((body.as_slice(), changed), (b"events".as_slice(), true));
+        assert_eq!(sent_headers(&cache)[IF_NONE_MATCH], "\"v1\"");
+
+        let (body, changed) = block_on(cache.body(URL, response(304, None, ""))).unwrap();
+        assert_eq!((body.as_slice(), changed), (b"events".as_slice(), false));
+    }
+
+    #[test]
+    fn not_modified_without_stored_body_is_an_error() {
+        let cache = ConditionalCache::new();
+        assert!(block_on(cache.body(URL, response(304, None, ""))).is_err());
+    }
+
+    #[test]
+    fn responses_without_validators_or_success_are_not_stored() {
+        let cache = ConditionalCache::new();
+
+        block_on(cache.body(URL, response(200, None, "events"))).unwrap();
+        block_on(cache.body(URL, response(500, Some("\"v1\""), "oops"))).unwrap();
+
+        assert!(sent_headers(&cache).is_empty());
+    }
+
+    #[test]
+    fn retain_forgets_unused_urls() {
+        let cache = ConditionalCache::new();
+        block_on(cache.body(URL, response(200, Some("\"v1\""), "events"))).unwrap();
+
+        cache.retain(|url| url != URL);
+
+        assert!(sent_headers(&cache).is_empty());
+    }
+
+    #[test]
+    fn oversized_responses_are_rejected_and_forgotten() {
+        let cache = ConditionalCache::new().with_max_body_size(6);
+        block_on(cache.body(URL, response(200, Some("\"v1\""), "events"))).unwrap();
+
+        assert!(block_on(cache.body(URL, response(200, Some("\"v2\""), "more events"))).is_err());
+
+        assert!(sent_headers(&cache).is_empty());
+        assert!(block_on(cache.body(URL, response(304, None, ""))).is_err());
+    }
+
+    #[tokio::test]
+    async fn bodies_can_be_kept_on_disk() {
+        let dir = std::env::temp_dir().join(format!("beacon-conditional-test-{}", std::process::id()));
+        let cache = ConditionalCache::on_disk(dir.clone());
+
+        cache.body(URL, response(200, Some("\"v1\""), "image")).await.unwrap();
+        assert_eq!(std::fs::read(body_path(&dir, URL)).unwrap(), b"image");
+
+        let (body, changed) = cache.body(URL, response(304, None, "")).await.unwrap();
+        assert_eq!((body.as_slice(), changed), (b"image".as_slice(), false));
+
+        cache.retain(|_| false);
+        assert!(!body_path(&dir, URL).exists());
+    }
+}
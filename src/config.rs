@@ -1,4 +1,10 @@
+use crate::auth::AuthConfig;
+use crate::pocketbase::CollectionQuery;
+use crate::recurrence;
+use chrono::{NaiveTime, Weekday};
+use chrono_tz::Tz;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -10,6 +16,518 @@ pub struct Settings {
     pub window_height: i32,
     pub slide_interval_seconds: u64,
     pub refresh_interval_minutes: u64,
+    #[serde(default)]
+    pub realtime_enabled: bool,
+    #[serde(default = "default_events_collection")]
+    pub events_collection: String,
+    #[serde(default)]
+    pub api_mode: ApiMode,
+    #[serde(default = "default_collection_filter")]
+    pub collection_filter: String,
+    #[serde(default = "default_collection_sort")]
+    pub collection_sort: String,
+    #[serde(default)]
+    pub collection_expand: String,
+    #[serde(default = "default_collection_per_page")]
+    pub collection_per_page: u32,
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    /// IANA zone name (e.g. "America/New_York") all times are shown in
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// How far ahead events with a `recurring_type` are expanded into instances
+    #[serde(default = "default_recurring_horizon_days")]
+    pub recurring_horizon_days: u32,
+    /// Language for dates and UI text: "en", "es" or "pt" (a region suffix like "pt_BR" is fine)
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// strftime-style patterns; empty means the locale's usual format
+    #[serde(default)]
+    pub date_format: String,
+    #[serde(default)]
+    pub time_format: String,
+    /// Replacements for built-in UI text, keyed by name (e.g. `loading_events`)
+    #[serde(default)]
+    pub ui_strings: HashMap<String, String>,
+    /// How many times per cycle a featured event is shown
+    #[serde(default = "default_featured_weight")]
+    pub featured_weight: u32,
+    /// Slide duration for featured events; unset means `slide_interval_seconds`
+    #[serde(default)]
+    pub featured_slide_interval_seconds: Option<u64>,
+    /// Weights and durations for specific events, first match wins
+    #[serde(default)]
+    pub slide_rules: Vec<SlideRule>,
+    /// How the next slide is chosen
+    #[serde(default)]
+    pub rotation: RotationMode,
+    /// For `soonest_first`: an event's share of screen time halves for every this many days until it starts
+    #[serde(default = "default_rotation_half_life_days")]
+    pub rotation_half_life_days: f64,
+    /// Most lines a title may wrap to before it is shrunk further or shortened
+    #[serde(default = "default_title_max_lines")]
+    pub title_max_lines: usize,
+    #[serde(default = "default_location_max_lines")]
+    pub location_max_lines: usize,
+    /// Size slide durations to the amount of text; unset means a fixed `slide_interval_seconds`
+    #[serde(default)]
+    pub reading_time: Option<ReadingTime>,
+    /// QR codes for an event's map link and registration page
+    #[serde(default)]
+    pub qr_codes: QrCodes,
+    /// Animation between slides
+    #[serde(default)]
+    pub transitions: Transitions,
+    /// Slides shown alongside the events: announcements, images, welcome screens and widgets
+    #[serde(default)]
+    pub slides: Vec<SlideConfig>,
+    /// Named lists of what to play, in order (`[[playlists.<name>]]`)
+    #[serde(default)]
+    pub playlists: HashMap<String, Vec<PlaylistEntry>>,
+    /// Playlist to play; unset means every event and slide, ordered by `rotation`
+    #[serde(default)]
+    pub playlist: Option<String>,
+    /// Times of the week with their own playlist or categories, first match wins;
+    /// outside all of them `playlist` applies
+    #[serde(default)]
+    pub schedule: Vec<ScheduleRule>,
+}
+
+/// What to show during part of the week (`[[schedule]]`), in `timezone`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleRule {
+    /// Shown in the log when the rule takes effect
+    #[serde(default)]
+    pub name: String,
+    /// e.g. `["sat"]` or `["mon", "tue", "wed", "thu", "fri"]`; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// "HH:MM"; an `end` before `start` runs past midnight, and equal times mean all day
+    #[serde(default)]
+    pub start: NaiveTime,
+    #[serde(default)]
+    pub end: NaiveTime,
+    pub playlist: Option<String>,
+    /// Only show events in these categories (case-insensitive)
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// One step of a playlist.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaylistEntry {
+    /// Time on screen for each slide (or page) of this entry; unset means its usual duration
+    pub duration_seconds: Option<u64>,
+    /// How many times in a row the entry plays
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+    #[serde(flatten)]
+    pub content: PlaylistContent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PlaylistContent {
+    /// Every upcoming event, or only those in `category` (case-insensitive)
+    Events { category: Option<String> },
+    /// A slide from `[[slides]]`, by id
+    Slide { slide: String },
+    /// An announcement, image, welcome or widget slide written out in place
+    #[serde(untagged)]
+    Inline(SlideContent),
+}
+
+/// One slide that isn't an event (`[[slides]]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlideConfig {
+    /// Defaults to the slide's type and position in the list
+    pub id: Option<String>,
+    /// How many times per cycle the slide is shown
+    pub weight: Option<u32>,
+    /// Unset means reading time for announcements and `slide_interval_seconds` for the rest
+    pub duration_seconds: Option<u64>,
+    #[serde(flatten)]
+    pub content: SlideContent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SlideContent {
+    /// A heading and text, formatted like event descriptions
+    Announcement {
+        title: String,
+        #[serde(default)]
+        body: String,
+    },
+    /// A picture filling the screen; a file path or URL
+    Image {
+        image: String,
+        #[serde(default)]
+        fit: ImageFit,
+        caption: Option<String>,
+    },
+    /// Large centred text, e.g. a greeting for visitors
+    Welcome {
+        title: String,
+        #[serde(default)]
+        subtitle: String,
+    },
+    Widget {
+        widget: WidgetKind,
+    },
+    /// Several upcoming events at a glance, listed under their dates. With
+    /// neither limit set it covers the next 7 days.
+    Agenda {
+        /// Defaults to the translated "This Week"
+        #[serde(default)]
+        title: String,
+        /// Only events starting within this many days, today included
+        days: Option<u32>,
+        /// Only the next this many events
+        max_events: Option<usize>,
+        /// Most events listed on one page; fewer when they don't fit the screen
+        #[serde(default = "default_agenda_page_size")]
+        per_page: usize,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFit {
+    /// The whole image, with bars where the shape doesn't match the screen
+    #[default]
+    Contain,
+    /// Fills the screen, cropping the edges
+    Cover,
+}
+
+/// Live content drawn by the sign itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    /// Current time and date
+    Clock,
+}
+
+/// How QR codes are drawn on event slides (`[qr_codes]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QrCodes {
+    pub enabled: bool,
+    /// Width and height of each code in pixels, quiet zone included
+    pub size: f32,
+    pub placement: QrPlacement,
+}
+
+impl Default for QrCodes {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size: 180.0,
+            placement: QrPlacement::default(),
+        }
+    }
+}
+
+/// Corner of the event slide's image column the QR codes are drawn in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QrPlacement {
+    TopLeft,
+    TopRight,
+    /// Over the corner of the event image, where it hides the least text
+    #[default]
+    BottomLeft,
+    BottomRight,
+}
+
+/// Slide duration from word count (`[reading_time]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReadingTime {
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: f64,
+    #[serde(default = "default_min_slide_seconds")]
+    pub min_seconds: u64,
+    #[serde(default = "default_max_slide_seconds")]
+    pub max_seconds: u64,
+    /// Overrides keyed by category name (case-insensitive)
+    #[serde(default)]
+    pub categories: HashMap<String, ReadingTimeOverride>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReadingTimeOverride {
+    pub words_per_minute: Option<f64>,
+    pub min_seconds: Option<u64>,
+    pub max_seconds: Option<u64>,
+}
+
+impl ReadingTime {
+    pub fn duration(&self, words: usize, category: &str) -> Duration {
+        let overrides = self
+            .categories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .map(|(_, overrides)| overrides);
+
+        let words_per_minute = overrides
+            .and_then(|o| o.words_per_minute)
+            .unwrap_or(self.words_per_minute)
+            .max(1.0);
+        let min = overrides.and_then(|o| o.min_seconds).unwrap_or(self.min_seconds);
+        let max = overrides.and_then(|o| o.max_seconds).unwrap_or(self.max_seconds).max(min);
+
+        let seconds = words as f64 / words_per_minute * 60.0;
+        Duration::from_secs_f64(seconds.clamp(min as f64, max as f64))
+    }
+}
+
+/// Slide ordering for this display.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationMode {
+    /// In start-time order, repeating weighted events within the cycle
+    #[default]
+    Chronological,
+    /// Featured events first, then the rest in start-time order
+    FeaturedFirst,
+    /// Random in proportion to weight, never the same slide twice in a row
+    WeightedRandom,
+    /// Events of the same category together
+    ByCategory,
+    /// Events starting soon shown most, later ones less and less
+    SoonestFirst,
+}
+
+/// How one slide gives way to the next (`[transitions]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Transitions {
+    pub style: TransitionStyle,
+    pub duration_ms: u64,
+    pub easing: Easing,
+    /// Turns every animation off, whatever `style` says
+    pub reduced_motion: bool,
+}
+
+impl Default for Transitions {
+    fn default() -> Self {
+        Self {
+            style: TransitionStyle::default(),
+            duration_ms: 600,
+            easing: Easing::default(),
+            reduced_motion: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionStyle {
+    /// Switch instantly
+    None,
+    /// The new slide fades in as the old one fades out
+    #[default]
+    Crossfade,
+    /// The new slide pushes the old one out to the left
+    Slide,
+    /// The new slide grows into place while the old one grows and fades away
+    Zoom,
+    /// The old slide fades to the background, then the new one fades in
+    Dissolve,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+/// Screen time for events matched by id or title (`[[slide_rules]]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlideRule {
+    /// Event id; also matches every instance of a recurring event
+    pub id: Option<String>,
+    /// Case-insensitive text the title must contain
+    pub title: Option<String>,
+    pub weight: Option<u32>,
+    pub slide_interval_seconds: Option<u64>,
+    /// Sign-up or info page shown as a QR code, for events whose source has no such link
+    pub registration_url: Option<String>,
+}
+
+impl SlideRule {
+    fn matches(&self, id: &str, title: &str) -> bool {
+        if self.id.is_none() && self.title.is_none() {
+            return false;
+        }
+        self.id.as_deref().map_or(true, |rule_id| recurrence::is_same_series(id, rule_id))
+            && self.title.as_deref().map_or(true, |text| {
+                title.to_lowercase().contains(&text.to_lowercase())
+            })
+    }
+}
+
+/// One entry of the `[[sources]]` registry. Events from every source are
+/// merged into a single rotation.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceConfig {
+    Pocketbase(PocketbaseSource),
+    Ical(IcalSourceConfig),
+    Local(LocalSourceConfig),
+}
+
+impl SourceConfig {
+    pub fn name(&self) -> &str {
+        match self {
+            SourceConfig::Pocketbase(source) => &source.name,
+            SourceConfig::Ical(source) => &source.name,
+            SourceConfig::Local(source) => &source.name,
+        }
+    }
+}
+
+/// An iCalendar feed (`url`) or file (`path`); exactly one must be set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IcalSourceConfig {
+    pub name: String,
+    pub url: Option<String>,
+    pub path: Option<PathBuf>,
+    /// How far ahead recurring events are expanded
+    #[serde(default = "default_ical_horizon_days")]
+    pub horizon_days: u32,
+    /// Category for events that have no CATEGORIES property
+    #[serde(default)]
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PocketbaseSource {
+    pub name: String,
+    pub api_url: String,
+    #[serde(default)]
+    pub api_mode: ApiMode,
+    #[serde(default = "default_events_collection")]
+    pub collection: String,
+    #[serde(default = "default_collection_filter")]
+    pub filter: String,
+    #[serde(default = "default_collection_sort")]
+    pub sort: String,
+    #[serde(default)]
+    pub expand: String,
+    #[serde(default = "default_collection_per_page")]
+    pub per_page: u32,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+}
+
+impl PocketbaseSource {
+    pub fn collection_query(&self) -> CollectionQuery {
+        // Empty strings in the config mean "leave this parameter out"
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+        CollectionQuery {
+            name: self.collection.clone(),
+            filter: non_empty(&self.filter),
+            sort: non_empty(&self.sort),
+            expand: non_empty(&self.expand),
+            per_page: self.per_page,
+        }
+    }
+}
+
+/// Which PocketBase API the events are read from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiMode {
+    /// Custom `/api/events/upcoming` endpoint
+    #[default]
+    Upcoming,
+    /// Stock `/api/collections/{events_collection}/records` API
+    Collection,
+}
+
+/// A folder with an `events.json`/`events.toml` manifest and its images.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalSourceConfig {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+fn default_timezone() -> String {
+    String::from("America/New_York")
+}
+
+fn default_recurring_horizon_days() -> u32 {
+    60
+}
+
+fn default_featured_weight() -> u32 {
+    2
+}
+
+fn default_rotation_half_life_days() -> f64 {
+    7.0
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+fn default_agenda_page_size() -> usize {
+    6
+}
+
+fn default_title_max_lines() -> usize {
+    2
+}
+
+fn default_location_max_lines() -> usize {
+    1
+}
+
+fn default_words_per_minute() -> f64 {
+    // Slower than normal reading: people are standing, often a few metres away
+    180.0
+}
+
+fn default_min_slide_seconds() -> u64 {
+    8
+}
+
+fn default_max_slide_seconds() -> u64 {
+    60
+}
+
+fn default_locale() -> String {
+    String::from("en")
+}
+
+fn default_events_collection() -> String {
+    String::from("events")
+}
+
+fn default_collection_filter() -> String {
+    String::from("end_time >= @now")
+}
+
+fn default_collection_sort() -> String {
+    String::from("start_time")
+}
+
+fn default_collection_per_page() -> u32 {
+    100
+}
+
+fn default_ical_horizon_days() -> u32 {
+    60
 }
 
 impl Settings {
@@ -24,10 +542,78 @@ impl Settings {
         Duration::from_secs(self.slide_interval_seconds)
     }
 
+    /// How many times per rotation cycle an event is shown.
+    pub fn slide_weight(&self, id: &str, title: &str, is_featured: bool) -> u32 {
+        let rule = self.slide_rules.iter().find(|rule| rule.matches(id, title));
+        match rule.and_then(|rule| rule.weight) {
+            Some(weight) => weight.max(1),
+            None if is_featured => self.featured_weight.max(1),
+            None => 1,
+        }
+    }
+
+    /// How long an event stays on screen each time it is shown: a matching
+    /// slide rule or the featured duration if set, otherwise the reading time
+    /// for its `words`, otherwise `slide_interval_seconds`.
+    pub fn slide_duration(&self, id: &str, title: &str, category: &str, is_featured: bool, words: usize) -> Duration {
+        let rule = self.slide_rules.iter().find(|rule| rule.matches(id, title));
+        rule.and_then(|rule| rule.slide_interval_seconds)
+            .or(if is_featured { self.featured_slide_interval_seconds } else { None })
+            .map(Duration::from_secs)
+            .or_else(|| self.reading_time.as_ref().map(|reading| reading.duration(words, category)))
+            .unwrap_or_else(|| self.slide_interval())
+    }
+
+    /// Registration link from the first slide rule that matches and has one.
+    pub fn registration_url(&self, id: &str, title: &str) -> Option<&str> {
+        self.slide_rules
+            .iter()
+            .filter(|rule| rule.matches(id, title))
+            .find_map(|rule| rule.registration_url.as_deref())
+    }
+
     pub fn refresh_interval(&self) -> Duration {
         Duration::from_secs(self.refresh_interval_minutes * 60)
     }
 
+    pub fn timezone(&self) -> Tz {
+        self.timezone.parse().unwrap_or_else(|e| {
+            tracing::error!("Invalid timezone '{}' ({}), using {}", self.timezone, e, default_timezone());
+            default_timezone().parse().unwrap_or(Tz::UTC)
+        })
+    }
+
+    /// The configured `[[sources]]`, or a single PocketBase source built from
+    /// the top-level API settings when none are declared.
+    pub fn source_configs(&self) -> Vec<SourceConfig> {
+        if !self.sources.is_empty() {
+            return self.sources.clone();
+        }
+
+        vec![SourceConfig::Pocketbase(PocketbaseSource {
+            name: String::from("default"),
+            api_url: self.api_url.clone(),
+            api_mode: self.api_mode,
+            collection: self.events_collection.clone(),
+            filter: self.collection_filter.clone(),
+            sort: self.collection_sort.clone(),
+            expand: self.collection_expand.clone(),
+            per_page: self.collection_per_page,
+            auth: self.auth.clone(),
+        })]
+    }
+
+    /// Folders of all local sources, which are watched for live reloads.
+    pub fn local_content_dirs(&self) -> Vec<PathBuf> {
+        self.sources
+            .iter()
+            .filter_map(|source| match source {
+                SourceConfig::Local(local) => Some(local.path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn config_path() -> anyhow::Result<PathBuf> {
         let mut path = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
@@ -45,6 +631,137 @@ impl Default for Settings {
             window_height: 1080,
             slide_interval_seconds: 10,
             refresh_interval_minutes: 5,
+            realtime_enabled: false,
+            events_collection: default_events_collection(),
+            api_mode: ApiMode::default(),
+            collection_filter: default_collection_filter(),
+            collection_sort: default_collection_sort(),
+            collection_expand: String::new(),
+            collection_per_page: default_collection_per_page(),
+            sources: Vec::new(),
+            auth: None,
+            timezone: default_timezone(),
+            recurring_horizon_days: default_recurring_horizon_days(),
+            locale: default_locale(),
+            date_format: String::new(),
+            time_format: String::new(),
+            ui_strings: HashMap::new(),
+            featured_weight: default_featured_weight(),
+            featured_slide_interval_seconds: None,
+            slide_rules: Vec::new(),
+            rotation: RotationMode::default(),
+            rotation_half_life_days: default_rotation_half_life_days(),
+            title_max_lines: default_title_max_lines(),
+            location_max_lines: default_location_max_lines(),
+            reading_time: None,
+            qr_codes: QrCodes::default(),
+            transitions: Transitions::default(),
+            slides: Vec::new(),
+            playlists: HashMap::new(),
+            playlist: None,
+            schedule: Vec::new(),
         }
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading_time() -> ReadingTime {
+        toml::from_str(
+            r#"
+            words_per_minute = 120
+            [categories.announcements]
+            max_seconds = 20
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reading_time_is_clamped() {
+        let reading = reading_time();
+        assert_eq!(reading.duration(40, "Worship"), Duration::from_secs(20));
+        assert_eq!(reading.duration(2, "Worship"), Duration::from_secs(8));
+        assert_eq!(reading.duration(1000, "Worship"), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn category_overrides_ignore_case() {
+        assert_eq!(reading_time().duration(1000, "Announcements"), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn rules_and_featured_duration_beat_reading_time() {
+        let settings = Settings {
+            featured_slide_interval_seconds: Some(30),
+            slide_rules: vec![SlideRule {
+                id: None,
+                title: Some(String::from("potluck")),
+                weight: None,
+                slide_interval_seconds: Some(45),
+                registration_url: None,
+            }],
+            reading_time: Some(reading_time()),
+            ..Settings::default()
+        };
+
+        assert_eq!(settings.slide_duration("a", "Church Potluck", "", true, 10), Duration::from_secs(45));
+        assert_eq!(settings.slide_duration("b", "Choir", "", true, 10), Duration::from_secs(30));
+        assert_eq!(settings.slide_duration("c", "Choir", "", false, 40), Duration::from_secs(20));
+
+        let fixed = Settings::default();
+        assert_eq!(fixed.slide_duration("c", "Choir", "", false, 40), fixed.slide_interval());
+    }
+
+    #[test]
+    fn registration_url_comes_from_the_first_rule_with_one() {
+        let rule = |weight, registration_url: Option<&str>| SlideRule {
+            id: None,
+            title: Some(String::from("retreat")),
+            weight,
+            slide_interval_seconds: None,
+            registration_url: registration_url.map(String::from),
+        };
+        let rules = vec![rule(Some(2), None), rule(None, Some("https://example.com/retreat"))];
+        let settings = Settings { slide_rules: rules, ..Settings::default() };
+
+        assert_eq!(settings.registration_url("a", "Youth Retreat"), Some("https://example.com/retreat"));
+        assert_eq!(settings.registration_url("b", "Choir"), None);
+    }
+
+    #[test]
+    fn qr_codes_default_to_the_image_corner() {
+        let qr: QrCodes = toml::from_str("size = 120").unwrap();
+        assert!(qr.enabled);
+        assert_eq!(qr.size, 120.0);
+        assert_eq!(qr.placement, QrPlacement::BottomLeft);
+    }
+
+    #[test]
+    fn slides_are_read_by_type() {
+        let slides: Vec<SlideConfig> = toml::from_str::<HashMap<String, Vec<SlideConfig>>>(
+            r#"
+            [[slides]]
+            type = "announcement"
+            title = "Parking"
+            duration_seconds = 15
+            [[slides]]
+            type = "image"
+            image = "slides/poster.png"
+            [[slides]]
+            type = "widget"
+            widget = "clock"
+            "#,
+        )
+        .unwrap()
+        .remove("slides")
+        .unwrap();
+
+        assert!(matches!(&slides[0].content, SlideContent::Announcement { title, body } if title == "Parking" && body.is_empty()));
+        assert_eq!(slides[0].duration_seconds, Some(15));
+        assert!(matches!(slides[1].content, SlideContent::Image { fit: ImageFit::Contain, caption: None, .. }));
+        assert!(matches!(slides[2].content, SlideContent::Widget { widget: WidgetKind::Clock }));
+    }
+}
@@ -0,0 +1,151 @@
+use iced::advanced::text::{self, Paragraph as _};
+use iced::{alignment, Font, Pixels, Size};
+
+type Paragraph = <iced::Renderer as text::Renderer>::Paragraph;
+
+const SIZE_STEP: f32 = 2.0;
+const ELLIPSIS: char = '…';
+
+/// Where a piece of text has to fit and how large it may be drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub width: f32,
+    pub max_lines: usize,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub font: Font,
+}
+
+/// Text sized (and if need be shortened) to fit an `Area`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fitted {
+    pub text: String,
+    pub size: f32,
+}
+
+/// Picks the largest size at which `content` fits in the area, measured with
+/// the real font. If it doesn't fit even at the smallest size, it is cut
+/// short with an ellipsis, at a word boundary where possible.
+pub fn fit(content: &str, area: Area) -> Fitted {
+    let mut size = area.max_size;
+    while size >= area.min_size {
+        if fits(content, size, area) {
+            return Fitted { text: content.to_string(), size };
+        }
+        size -= SIZE_STEP;
+    }
+
+    let size = area.min_size;
+    let chars: Vec<char> = content.chars().collect();
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if fits(&shortened(&chars, mid), size, area) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let text = at_word_boundary(&chars, low);
+    tracing::debug!("'{}' doesn't fit in {} line(s), showing '{}'", content, area.max_lines, text);
+    Fitted { text, size }
+}
+
+fn fits(content: &str, size: f32, area: Area) -> bool {
+    let paragraph = Paragraph::with_text(text::Text {
+        content,
+        bounds: Size::new(area.width, f32::INFINITY),
+        size: Pixels(size),
+        line_height: text::LineHeight::default(),
+        font: area.font,
+        horizontal_alignment: alignment::Horizontal::Left,
+        vertical_alignment: alignment::Vertical::Top,
+        // Same as the text widget defaults, so we measure what gets drawn
+        shaping: text::Shaping::Basic,
+        wrapping: text::Wrapping::Word,
+    });
+
+    let bounds = paragraph.min_bounds();
+    let line_height = text::LineHeight::default().to_absolute(Pixels(size)).0;
+    let lines = (bounds.height / line_height).round() as usize;
+
+    // A single word wider than the area overflows instead of wrapping
+    lines <= area.max_lines && bounds.width <= area.width.ceil()
+}
+
+/// Height of rich text wrapped to `width`, measured with the real fonts.
+pub fn height<Link>(spans: &[text::Span<'_, Link, Font>], size: f32, width: f32) -> f32 {
+    let paragraph = Paragraph::with_spans(text::Text {
+        content: spans,
+        bounds: Size::new(width, f32::INFINITY),
+        size: Pixels(size),
+        line_height: text::LineHeight::default(),
+        font: Font::DEFAULT,
+        horizontal_alignment: alignment::Horizontal::Left,
+        vertical_alignment: alignment::Vertical::Top,
+        // What rich_text uses
+        shaping: text::Shaping::Advanced,
+        wrapping: text::Wrapping::Word,
+    });
+
+    paragraph.min_bounds().height
+}
+
+fn shortened(chars: &[char], count: usize) -> String {
+    let mut text: String = chars[..count].iter().collect();
+    text.truncate(text.trim_end().len());
+    text.push(ELLIPSIS);
+    text
+}
+
+/// Like `shortened`, but backs up to the last space unless that would
+/// throw away more than half of what fits.
+fn at_word_boundary(chars: &[char], count: usize) -> String {
+    let boundary = chars[..count]
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .filter(|index| *index >= count / 2)
+        .unwrap_or(count);
+    shortened(chars, boundary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    fn area(width: f32, max_lines: usize) -> Area {
+        Area { width, max_lines, min_size: 20.0, max_size: 40.0, font: Font::DEFAULT }
+    }
+
+    #[test]
+    fn shortened_text_ends_in_an_ellipsis() {
+        assert_eq!(shortened(&chars("Potluck dinner"), 8), "Potluck…");
+        assert_eq!(shortened(&chars("Potluck dinner"), 14), "Potluck dinner…");
+    }
+
+    #[test]
+    fn cuts_at_a_word_boundary_when_close_enough() {
+        assert_eq!(at_word_boundary(&chars("Potluck dinner and games"), 17), "Potluck dinner…");
+        // Backing up to the only space would lose most of the text
+        assert_eq!(at_word_boundary(&chars("A fellowshipdinner"), 15), "A fellowshipdin…");
+    }
+
+    #[test]
+    fn short_text_keeps_the_largest_size() {
+        assert_eq!(fit("Choir", area(2000.0, 1)), Fitted { text: String::from("Choir"), size: 40.0 });
+    }
+
+    #[test]
+    fn long_text_shrinks_then_is_shortened() {
+        let title = "Annual congregational meeting and potluck dinner in the fellowship hall";
+        let fitted = fit(title, area(300.0, 1));
+        assert_eq!(fitted.size, 20.0);
+        assert!(fitted.text.ends_with(ELLIPSIS));
+        assert!(title.starts_with(fitted.text.trim_end_matches(ELLIPSIS)));
+    }
+}
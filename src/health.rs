@@ -0,0 +1,190 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+// Up to this many consecutive failures the sign is "Degraded", after that "Offline"
+const DEGRADED_FAILURE_LIMIT: u32 = 3;
+// Spread retries by ±20% so several displays don't hammer the server in lockstep
+const JITTER_FRACTION: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Online,
+    Degraded,
+    Offline,
+}
+
+/// The server asked us to wait before trying again (HTTP `Retry-After`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RetryAfter(pub Duration);
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "server asked to retry after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for RetryAfter {}
+
+impl RetryAfter {
+    /// Reads a `Retry-After` header given in seconds. The HTTP-date form is
+    /// rare for APIs and falls back to the normal backoff.
+    pub fn from_response(response: &reqwest::Response) -> Option<Self> {
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|secs| RetryAfter(Duration::from_secs(secs)))
+    }
+
+    /// Finds a `RetryAfter` attached to an error, including under added context.
+    pub fn from_error(error: &anyhow::Error) -> Option<Self> {
+        error.downcast_ref::<RetryAfter>().copied()
+    }
+
+    /// Wraps an HTTP error status, keeping the `Retry-After` hint if there was one.
+    pub fn attach(error: reqwest::Error, retry_after: Option<RetryAfter>) -> anyhow::Error {
+        match retry_after {
+            Some(retry_after) => anyhow::Error::new(retry_after).context(error.to_string()),
+            None => error.into(),
+        }
+    }
+}
+
+/// Tracks whether event fetches are succeeding and when to try again.
+#[derive(Debug)]
+pub struct ConnectionHealth {
+    state: ConnectionState,
+    consecutive_failures: u32,
+    next_retry: Option<Instant>,
+}
+
+impl ConnectionHealth {
+    pub fn new() -> Self {
+        Self {
+            state: ConnectionState::Online,
+            consecutive_failures: 0,
+            next_retry: None,
+        }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Whether a failed fetch is waiting to be retried.
+    pub fn is_retrying(&self) -> bool {
+        self.next_retry.is_some()
+    }
+
+    pub fn retry_due(&self) -> bool {
+        self.next_retry.map_or(false, |at| Instant::now() >= at)
+    }
+
+    pub fn record_success(&mut self) {
+        if self.state != ConnectionState::Online {
+            tracing::info!("Connection restored after {} failed attempts", self.consecutive_failures);
+        }
+        self.state = ConnectionState::Online;
+        self.consecutive_failures = 0;
+        self.next_retry = None;
+    }
+
+    /// Schedules the next attempt and returns how long until then.
+    pub fn record_failure(&mut self, retry_after: Option<RetryAfter>) -> Duration {
+        self.consecutive_failures += 1;
+        self.state = if self.consecutive_failures <= DEGRADED_FAILURE_LIMIT {
+            ConnectionState::Degraded
+        } else {
+            ConnectionState::Offline
+        };
+
+        let delay = match retry_after {
+            Some(RetryAfter(requested)) => requested.max(self.backoff()),
+            None => self.backoff(),
+        };
+        self.next_retry = Some(Instant::now() + delay);
+
+        tracing::warn!(
+            "Fetch failed {} time(s) in a row, connection {:?}, retrying in {:?}",
+            self.consecutive_failures,
+            self.state,
+            delay
+        );
+        delay
+    }
+
+    fn backoff(&self) -> Duration {
+        let exponent = self.consecutive_failures.saturating_sub(1).min(16);
+        let base = INITIAL_BACKOFF.saturating_mul(1 << exponent).min(MAX_BACKOFF);
+        let jitter = 1.0 + JITTER_FRACTION * (fastrand::f64() * 2.0 - 1.0);
+        base.mul_f64(jitter)
+    }
+}
+
+impl Default for ConnectionHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within_jitter(delay: Duration, base: Duration) -> bool {
+        delay >= base.mul_f64(1.0 - JITTER_FRACTION) && delay <= base.mul_f64(1.0 + JITTER_FRACTION)
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        let mut health = ConnectionHealth::new();
+
+        for expected in [5, 10, 20, 40, 80, 160, 300, 300] {
+            let delay = health.record_failure(None);
+            assert!(within_jitter(delay, Duration::from_secs(expected)), "{:?} vs {}s", delay, expected);
+        }
+    }
+
+    #[test]
+    fn longer_retry_after_wins() {
+        let mut health = ConnectionHealth::new();
+
+        let delay = health.record_failure(Some(RetryAfter(Duration::from_secs(120))));
+        assert_eq!(delay, Duration::from_secs(120));
+
+        let delay = health.record_failure(Some(RetryAfter(Duration::from_secs(1))));
+        assert!(within_jitter(delay, INITIAL_BACKOFF * 2));
+    }
+
+    #[test]
+    fn repeated_failures_go_offline_until_a_success() {
+        let mut health = ConnectionHealth::new();
+
+        for _ in 0..DEGRADED_FAILURE_LIMIT {
+            health.record_failure(None);
+            assert_eq!(health.state(), ConnectionState::Degraded);
+        }
+        health.record_failure(None);
+        assert_eq!(health.state(), ConnectionState::Offline);
+        assert!(health.is_retrying());
+        assert!(!health.retry_due());
+
+        health.record_success();
+        assert_eq!(health.state(), ConnectionState::Online);
+        assert!(!health.is_retrying());
+        assert!(within_jitter(health.record_failure(None), INITIAL_BACKOFF));
+    }
+
+    #[test]
+    fn retry_after_survives_added_context() {
+        let error = anyhow::Error::new(RetryAfter(Duration::from_secs(30))).context("HTTP 429");
+        assert_eq!(RetryAfter::from_error(&error), Some(RetryAfter(Duration::from_secs(30))));
+        assert_eq!(RetryAfter::from_error(&anyhow::anyhow!("timeout")), None);
+    }
+}
@@ -0,0 +1,657 @@
+use crate::pocketbase::ApiEvent;
+use crate::recurrence::Rule;
+use crate::source::EventSource;
+use crate::timezone;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use futures::future::BoxFuture;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Where the calendar data lives.
+#[derive(Debug, Clone)]
+pub enum CalendarLocation {
+    Url(String),
+    Path(PathBuf),
+}
+
+/// Reads VEVENTs from an RFC 5545 iCalendar feed or file.
+#[derive(Debug, Clone)]
+pub struct IcalSource {
+    client: reqwest::Client,
+    location: CalendarLocation,
+    horizon: Duration,
+    default_category: String,
+    timezone: Tz,
+}
+
+impl IcalSource {
+    pub fn new(location: CalendarLocation, horizon_days: u32, default_category: String, timezone: Tz) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            location,
+            horizon: Duration::days(horizon_days as i64),
+            default_category,
+            timezone,
+        }
+    }
+
+    pub async fn fetch_events(&self) -> Result<Vec<ApiEvent>> {
+        let data = match &self.location {
+            CalendarLocation::Url(url) => {
+                // webcal:// is just a hint for calendar apps, the feed itself is plain HTTP(S)
+                let url = match url.strip_prefix("webcal://") {
+                    Some(rest) => format!("https://{}", rest),
+                    None => url.clone(),
+                };
+                tracing::info!("Fetching calendar from URL: {}", url);
+                self.client.get(&url).send().await?.error_for_status()?.text().await?
+            }
+            CalendarLocation::Path(path) => {
+                tracing::info!("Reading calendar from file: {}", path.display());
+                tokio::fs::read_to_string(path).await?
+            }
+        };
+
+        let now = Utc::now();
+        let events = parse_calendar(&data, now, now + self.horizon, &self.default_category, self.timezone)?;
+        tracing::info!("Parsed {} upcoming events from calendar", events.len());
+        Ok(events)
+    }
+}
+
+impl EventSource for IcalSource {
+    fn fetch_events(&self) -> BoxFuture<'_, Result<Vec<ApiEvent>>> {
+        Box::pin(IcalSource::fetch_events(self))
+    }
+}
+
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+#[derive(Default)]
+struct VEvent {
+    properties: Vec<Property>,
+}
+
+impl VEvent {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(|p| unescape_text(&p.value))
+    }
+
+    fn all(&self, name: &str) -> impl Iterator<Item = &Property> {
+        let name = name.to_string();
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+}
+
+/// A date or date-time value from DTSTART/DTEND, kept in wall-clock form
+/// until it is placed on the timeline.
+#[derive(Clone, Copy)]
+enum CalendarTime {
+    Utc(NaiveDateTime),
+    /// Qualified with a TZID we recognise
+    Zoned(NaiveDateTime, Tz),
+    /// No zone at all, read as the display's own zone
+    Floating(NaiveDateTime),
+    Date(NaiveDate),
+}
+
+impl CalendarTime {
+    fn parse(property: &Property) -> Option<Self> {
+        let value = property.value.trim();
+        let is_date = property.params.get("VALUE").map_or(false, |v| v == "DATE") || value.len() == 8;
+
+        if is_date {
+            return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(CalendarTime::Date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(CalendarTime::Utc);
+        }
+        let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+
+        // Custom VTIMEZONE names we can't resolve are treated like floating times
+        match property.params.get("TZID").and_then(|tzid| tzid.parse::<Tz>().ok()) {
+            Some(tz) => Some(CalendarTime::Zoned(time, tz)),
+            None => Some(CalendarTime::Floating(time)),
+        }
+    }
+
+    fn is_date(&self) -> bool {
+        matches!(self, CalendarTime::Date(_))
+    }
+
+    /// Checks an occurrence, in wall-clock time in `zone`, against an EXDATE
+    /// value; a bare date removes the whole day.
+    fn excludes(&self, occurrence: &NaiveDateTime, zone: Tz) -> bool {
+        match self {
+            CalendarTime::Date(date) => occurrence.date() == *date,
+            other => other.wall_clock(zone) == *occurrence,
+        }
+    }
+
+    /// The zone a series starting at this time repeats in (RFC 5545 3.3.10):
+    /// its TZID, UTC for `Z` times, and the display zone `tz` for floating times.
+    fn zone(&self, tz: Tz) -> Tz {
+        match self {
+            CalendarTime::Utc(_) => chrono_tz::UTC,
+            CalendarTime::Zoned(_, zone) => *zone,
+            CalendarTime::Floating(_) | CalendarTime::Date(_) => tz,
+        }
+    }
+
+    /// The instant this time refers to; floating times and dates are read in `tz`.
+    fn to_utc(self, tz: Tz) -> DateTime<Utc> {
+        let zone = self.zone(tz);
+        timezone::local_to_utc(self.wall_clock(zone), zone)
+    }
+
+    /// Wall-clock time in the zone `tz`.
+    fn wall_clock(&self, tz: Tz) -> NaiveDateTime {
+        match self {
+            CalendarTime::Utc(utc) => timezone::utc_to_local(utc.and_utc(), tz),
+            CalendarTime::Zoned(time, zone) => timezone::utc_to_local(timezone::local_to_utc(*time, *zone), tz),
+            CalendarTime::Floating(time) => *time,
+            CalendarTime::Date(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+fn parse_until(value: &str, tz: Tz) -> Option<NaiveDateTime> {
+    let property = Property {
+        name: String::from("UNTIL"),
+        params: HashMap::new(),
+        value: value.to_string(),
+    };
+    CalendarTime::parse(&property).map(|time| match time {
+        // An UNTIL date includes the whole day
+        CalendarTime::Date(date) => date.and_hms_opt(23, 59, 59).unwrap_or_default(),
+        other => other.wall_clock(tz),
+    })
+}
+
+/// Parses a calendar and returns every event (or recurrence instance) that
+/// has not ended by `now` and starts before `horizon`.
+pub fn parse_calendar(
+    data: &str,
+    now: DateTime<Utc>,
+    horizon: DateTime<Utc>,
+    default_category: &str,
+    tz: Tz,
+) -> Result<Vec<ApiEvent>> {
+    let vevents = parse_vevents(data);
+    if vevents.is_empty() && !data.contains("BEGIN:VCALENDAR") {
+        return Err(anyhow::anyhow!("Not an iCalendar document"));
+    }
+
+    // Modified instances of a recurring event carry the original start in RECURRENCE-ID
+    let mut overridden: HashSet<(String, DateTime<Utc>)> = HashSet::new();
+    for vevent in &vevents {
+        if let (Some(uid), Some(recurrence_id)) = (vevent.text("UID"), vevent.get("RECURRENCE-ID")) {
+            if let Some(time) = CalendarTime::parse(recurrence_id) {
+                overridden.insert((uid, time.to_utc(tz)));
+            }
+        }
+    }
+
+    let mut events = Vec::new();
+
+    for vevent in &vevents {
+        if vevent.text("STATUS").map_or(false, |s| s.eq_ignore_ascii_case("CANCELLED")) {
+            continue;
+        }
+
+        let Some(start) = vevent.get("DTSTART").and_then(CalendarTime::parse) else {
+            tracing::warn!("Skipping VEVENT without a valid DTSTART");
+            continue;
+        };
+        let uid = vevent.text("UID").unwrap_or_else(|| {
+            format!("{}-{}", vevent.text("SUMMARY").unwrap_or_default(), start.wall_clock(tz))
+        });
+        // Occurrences are worked out in wall-clock time in the zone of DTSTART
+        let zone = start.zone(tz);
+        let length = event_length(vevent, start, zone);
+        let recurrence_id = vevent.get("RECURRENCE-ID").and_then(CalendarTime::parse);
+
+        let rule = match (vevent.get("RRULE"), recurrence_id) {
+            (Some(rrule), None) => match Rule::parse(&rrule.value, |until| parse_until(until, zone)) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    tracing::warn!("Unsupported RRULE on '{}', showing the first instance only: {}", uid, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let starts = match &rule {
+            Some(rule) => {
+                let excluded = excluded_dates(vevent);
+                let from = timezone::utc_to_local(now, zone) - length;
+                rule.occurrences(start.wall_clock(zone), from, timezone::utc_to_local(horizon, zone))
+                    .into_iter()
+                    .filter(|occurrence| !excluded.iter().any(|ex| ex.excludes(occurrence, zone)))
+                    .filter(|occurrence| {
+                        !overridden.contains(&(uid.clone(), timezone::local_to_utc(*occurrence, zone)))
+                    })
+                    .collect()
+            }
+            None => vec![start.wall_clock(zone)],
+        };
+
+        for local_start in starts {
+            let start_time = timezone::local_to_utc(local_start, zone);
+            let end_time = timezone::local_to_utc(local_start + length, zone);
+            if end_time <= now || start_time > horizon {
+                continue;
+            }
+
+            // Recurrence instances need their own stable id so they can be told apart,
+            // and a modified instance keeps the id of the occurrence it replaces
+            let id = match (&rule, recurrence_id) {
+                (Some(_), _) => format!("{}@{}", uid, start_time.timestamp()),
+                (None, Some(original)) => format!("{}@{}", uid, original.to_utc(tz).timestamp()),
+                (None, None) => uid.clone(),
+            };
+            events.push(build_event(vevent, id, start_time, end_time, default_category, tz));
+        }
+    }
+
+    events.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    Ok(events)
+}
+
+fn excluded_dates(vevent: &VEvent) -> Vec<CalendarTime> {
+    vevent
+        .all("EXDATE")
+        .flat_map(|property| {
+            property.value.split(',').filter_map(|value| {
+                CalendarTime::parse(&Property {
+                    name: property.name.clone(),
+                    params: property.params.clone(),
+                    value: value.to_string(),
+                })
+            }).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Length in wall-clock time in `zone`, so it can be added to each occurrence.
+fn event_length(vevent: &VEvent, start: CalendarTime, zone: Tz) -> Duration {
+    if let Some(end) = vevent.get("DTEND").and_then(CalendarTime::parse) {
+        return end.wall_clock(zone) - start.wall_clock(zone);
+    }
+    if let Some(duration) = vevent.get("DURATION").and_then(|p| parse_duration(&p.value)) {
+        return duration;
+    }
+    // RFC 5545: an all-day event without an end lasts one day, a timed one is instantaneous
+    if start.is_date() {
+        Duration::days(1)
+    } else {
+        Duration::zero()
+    }
+}
+
+fn build_event(
+    vevent: &VEvent,
+    id: String,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    default_category: &str,
+    tz: Tz,
+) -> ApiEvent {
+    // Split before unescaping so `\,` stays part of a category
+    let category = vevent
+        .get("CATEGORIES")
+        .and_then(|categories| split_escaped(&categories.value, ',').first().map(|c| unescape_text(c).trim().to_string()))
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| default_category.to_string());
+
+    let stamp = |name: &str| {
+        vevent
+            .get(name)
+            .and_then(CalendarTime::parse)
+            .map(|t| t.to_utc(tz))
+    };
+    let updated_at = stamp("LAST-MODIFIED").or_else(|| stamp("DTSTAMP")).unwrap_or(start_time);
+    let created_at = stamp("CREATED").unwrap_or(updated_at);
+
+    ApiEvent {
+        id,
+        title: vevent.text("SUMMARY").unwrap_or_default(),
+        description: vevent.text("DESCRIPTION").unwrap_or_default(),
+        start_time,
+        end_time,
+        location: vevent.text("LOCATION").unwrap_or_default(),
+        location_url: vevent.text("URL").filter(|url| !url.is_empty()),
+        registration_url: None,
+        image: None,
+        thumbnail: None,
+        category,
+        is_featured: false,
+        recurring_type: None,
+        created_at,
+        updated_at,
+    }
+}
+
+fn parse_vevents(data: &str) -> Vec<VEvent> {
+    let mut vevents = Vec::new();
+    let mut current: Option<VEvent> = None;
+    // Nested components (VALARM) inside a VEVENT must not leak their properties
+    let mut nested_depth = 0;
+
+    for line in unfold_lines(data) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+
+        match (property.name.as_str(), property.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(VEvent::default()),
+            ("END", "VEVENT") => {
+                if let Some(vevent) = current.take() {
+                    vevents.push(vevent);
+                }
+                nested_depth = 0;
+            }
+            ("BEGIN", _) if current.is_some() => nested_depth += 1,
+            ("END", _) if current.is_some() => nested_depth -= 1,
+            _ => {
+                if let Some(vevent) = current.as_mut() {
+                    if nested_depth == 0 {
+                        vevent.properties.push(property);
+                    }
+                }
+            }
+        }
+    }
+
+    vevents
+}
+
+/// Joins folded content lines (continuations start with a space or tab).
+fn unfold_lines(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.lines() {
+        match line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+            Some(continuation) if !lines.is_empty() => {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(continuation);
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // The value starts at the first colon that isn't inside a quoted parameter
+    let split = unquoted(line, ':').next()?;
+
+    let (head, value) = (&line[..split], &line[split + 1..]);
+    let mut parts = split_unquoted(head, ';').into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, val)| (key.to_ascii_uppercase(), val.trim_matches('"').to_string()))
+        .collect();
+
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// Positions of `separator` outside double-quoted parameter values.
+fn unquoted(text: &str, separator: char) -> impl Iterator<Item = usize> + '_ {
+    let mut in_quotes = false;
+    text.char_indices().filter_map(move |(index, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        (c == separator && !in_quotes).then_some(index)
+    })
+}
+
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for index in unquoted(text, separator) {
+        parts.push(&text[start..index]);
+        start = index + separator.len_utf8();
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Splits a list value on the separators that aren't escaped with a backslash.
+fn split_escaped(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(&value[start..index]);
+            start = index + separator.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+/// Parses an RFC 5545 DURATION such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                total = total + match unit {
+                    'W' => Duration::weeks(amount),
+                    'D' => Duration::days(amount),
+                    'H' => Duration::hours(amount),
+                    'M' => Duration::minutes(amount),
+                    'S' => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(date: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap().and_utc()
+    }
+
+    fn calendar(vevents: &[&str]) -> String {
+        let mut data = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n");
+        for vevent in vevents {
+            data.push_str("BEGIN:VEVENT\r\n");
+            data.push_str(vevent);
+            data.push_str("END:VEVENT\r\n");
+        }
+        data.push_str("END:VCALENDAR\r\n");
+        data
+    }
+
+    fn parse(vevents: &[&str], now: &str, horizon: &str) -> Vec<ApiEvent> {
+        parse_calendar(&calendar(vevents), utc(now), utc(horizon), "Events", chrono_tz::America::New_York).unwrap()
+    }
+
+    #[test]
+    fn reads_event_properties() {
+        let events = parse(
+            &["UID:potluck\r\nSUMMARY:Potluck\\, then games\r\nDESCRIPTION:Bring a dish\\nand a friend\r\n \
+               of yours\r\nLOCATION:Hall\r\nCATEGORIES:Fellowship,Food\r\nDTSTART:20300101T180000Z\r\n\
+               DURATION:PT1H30M\r\nBEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\n"],
+            "2029-12-01 00:00",
+            "2030-02-01 00:00",
+        );
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.id, "potluck");
+        assert_eq!(event.title, "Potluck, then games");
+        assert_eq!(event.description, "Bring a dish\nand a friendof yours");
+        assert_eq!(event.category, "Fellowship");
+        assert_eq!(event.start_time, utc("2030-01-01 18:00"));
+        assert_eq!(event.end_time, utc("2030-01-01 19:30"));
+    }
+
+    #[test]
+    fn skips_cancelled_past_and_distant_events() {
+        let events = parse(
+            &[
+                "UID:cancelled\r\nSTATUS:CANCELLED\r\nDTSTART:20300101T180000Z\r\n",
+                "UID:past\r\nDTSTART:20290101T180000Z\r\n",
+                "UID:distant\r\nDTSTART:20310101T180000Z\r\n",
+                "UID:all-day\r\nDTSTART;VALUE=DATE:20300102\r\n",
+            ],
+            "2029-12-01 00:00",
+            "2030-02-01 00:00",
+        );
+        let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, ["all-day"]);
+        assert_eq!(events[0].end_time - events[0].start_time, Duration::days(1));
+    }
+
+    #[test]
+    fn expands_series_with_exceptions() {
+        let events = parse(
+            &[
+                "UID:choir\r\nSUMMARY:Choir\r\nDTSTART:20300103T180000Z\r\nDTEND:20300103T190000Z\r\n\
+                 RRULE:FREQ=WEEKLY\r\nEXDATE:20300110T180000Z\r\n",
+                "UID:choir\r\nSUMMARY:Choir (moved)\r\nRECURRENCE-ID:20300117T180000Z\r\n\
+                 DTSTART:20300118T180000Z\r\nDTEND:20300118T190000Z\r\n",
+            ],
+            "2030-01-01 00:00",
+            "2030-01-25 00:00",
+        );
+        let listed: Vec<_> = events.iter().map(|event| (event.id.as_str(), event.title.as_str())).collect();
+        let moved = format!("choir@{}", utc("2030-01-17 18:00").timestamp());
+        let first = format!("choir@{}", utc("2030-01-03 18:00").timestamp());
+        let last = format!("choir@{}", utc("2030-01-24 18:00").timestamp());
+        assert_eq!(
+            listed,
+            [(first.as_str(), "Choir"), (moved.as_str(), "Choir (moved)"), (last.as_str(), "Choir")]
+        );
+    }
+
+    #[test]
+    fn series_repeats_in_its_own_zone() {
+        // London moves to summer time on March 30th, New York already did on the 9th
+        let events = parse(
+            &["UID:choir\r\nDTSTART;TZID=Europe/London:20250102T100000\r\nDURATION:PT1H\r\nRRULE:FREQ=WEEKLY\r\n"],
+            "2025-03-20 00:00",
+            "2025-04-04 00:00",
+        );
+        let starts: Vec<_> = events.iter().map(|event| event.start_time).collect();
+        assert_eq!(starts, [utc("2025-03-20 10:00"), utc("2025-03-27 10:00"), utc("2025-04-03 09:00")]);
+    }
+
+    #[test]
+    fn utc_series_stays_in_utc() {
+        let events = parse(
+            &["UID:choir\r\nDTSTART:20250102T100000Z\r\nDURATION:PT1H\r\nRRULE:FREQ=WEEKLY\r\n"],
+            "2025-03-20 00:00",
+            "2025-04-04 00:00",
+        );
+        let starts: Vec<_> = events.iter().map(|event| event.start_time).collect();
+        assert_eq!(starts, [utc("2025-03-20 10:00"), utc("2025-03-27 10:00"), utc("2025-04-03 10:00")]);
+    }
+
+    #[test]
+    fn splits_properties() {
+        let property = parse_property("DTSTART;TZID=\"America/New_York\";VALUE=DATE-TIME:20300101T090000").unwrap();
+        assert_eq!(property.name, "DTSTART");
+        assert_eq!(property.params["TZID"], "America/New_York");
+        assert_eq!(property.params["VALUE"], "DATE-TIME");
+        assert_eq!(property.value, "20300101T090000");
+
+        let quoted = parse_property("ATTENDEE;CN=\"Smith: John\":mailto:john@example.com").unwrap();
+        assert_eq!(quoted.params["CN"], "Smith: John");
+        assert_eq!(quoted.value, "mailto:john@example.com");
+
+        let semicolon = parse_property("ORGANIZER;CN=\"Smith; John\";ROLE=CHAIR:mailto:john@example.com").unwrap();
+        assert_eq!(semicolon.params["CN"], "Smith; John");
+        assert_eq!(semicolon.params["ROLE"], "CHAIR");
+
+        assert!(parse_property("no colon here").is_none());
+    }
+
+    #[test]
+    fn escaped_commas_stay_in_the_category() {
+        let events = parse(
+            &["UID:lunch\r\nSUMMARY:Lunch\r\nCATEGORIES:Food\\, Fellowship,Games\r\nDTSTART:20300101T120000Z\r\n"],
+            "2029-12-01 00:00",
+            "2030-02-01 00:00",
+        );
+        assert_eq!(events[0].category, "Food, Fellowship");
+        assert_eq!(split_escaped("a\\\\,b\\,c", ','), ["a\\\\", "b\\,c"]);
+    }
+
+    #[test]
+    fn unfolds_continuation_lines() {
+        assert_eq!(unfold_lines("SUMMARY:Long\r\n  title\r\n\tcontinued\r\nUID:1"), ["SUMMARY:Long titlecontinued", "UID:1"]);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("P1DT12H"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+        assert_eq!(parse_duration("1H"), None);
+        assert_eq!(parse_duration("PT1X"), None);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::pocketbase::ApiEvent;
+use crate::source::EventSource;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream, StreamExt};
+use notify::Watcher;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const JSON_MANIFEST: &str = "events.json";
+const TOML_MANIFEST: &str = "events.toml";
+// Editors and file copies fire bursts of events, wait for them to settle before reloading
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Events and images read from a folder on disk, for displays without a network.
+///
+/// The folder holds an `events.json` or `events.toml` manifest next to the
+/// image files it refers to.
+#[derive(Debug, Clone)]
+pub struct LocalSource {
+    dir: PathBuf,
+}
+
+/// One manifest entry. Same field names as `ApiEvent`, but everything that
+/// is tedious to write by hand is optional.
+#[derive(Debug, Deserialize)]
+struct ManifestEvent {
+    id: Option<String>,
+    title: String,
+    #[serde(default)]
+    description: String,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    #[serde(default)]
+    location: String,
+    location_url: Option<String>,
+    #[serde(alias = "info_url")]
+    registration_url: Option<String>,
+    image: Option<String>,
+    thumbnail: Option<String>,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    is_featured: bool,
+    recurring_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Manifest {
+    List(Vec<ManifestEvent>),
+    Table { events: Vec<ManifestEvent> },
+}
+
+impl LocalSource {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub async fn fetch_events(&self) -> Result<Vec<ApiEvent>> {
+        let json_path = self.dir.join(JSON_MANIFEST);
+        let toml_path = self.dir.join(TOML_MANIFEST);
+
+        let manifest: Manifest = if json_path.exists() {
+            tracing::info!("Reading local events from {}", json_path.display());
+            serde_json::from_str(&tokio::fs::read_to_string(&json_path).await?)?
+        } else if toml_path.exists() {
+            tracing::info!("Reading local events from {}", toml_path.display());
+            toml::from_str(&tokio::fs::read_to_string(&toml_path).await?)?
+        } else {
+            return Err(anyhow::anyhow!(
+                "No {} or {} found in {}",
+                JSON_MANIFEST,
+                TOML_MANIFEST,
+                self.dir.display()
+            ));
+        };
+
+        let entries = match manifest {
+            Manifest::List(events) | Manifest::Table { events } => events,
+        };
+
+        let now = Utc::now();
+        let modified = self.manifest_modified(&json_path, &toml_path).unwrap_or(now);
+        let events: Vec<ApiEvent> = entries
+            .into_iter()
+            .filter(|entry| entry.end_time > now)
+            .map(|entry| self.to_api_event(entry, modified))
+            .collect();
+
+        tracing::info!("Loaded {} upcoming local events", events.len());
+        Ok(events)
+    }
+
+    fn manifest_modified(&self, json_path: &Path, toml_path: &Path) -> Option<DateTime<Utc>> {
+        let path = if json_path.exists() { json_path } else { toml_path };
+        std::fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from)
+    }
+
+    fn to_api_event(&self, entry: ManifestEvent, modified: DateTime<Utc>) -> ApiEvent {
+        let id = entry
+            .id
+            .unwrap_or_else(|| format!("local-{}-{}", entry.start_time.timestamp(), entry.title));
+
+        ApiEvent {
+            id,
+            title: entry.title,
+            description: entry.description,
+            start_time: entry.start_time,
+            end_time: entry.end_time,
+            location: entry.location,
+            location_url: entry.location_url,
+            registration_url: entry.registration_url,
+            image: entry.image.map(|image| self.resolve_image(&image)),
+            thumbnail: entry.thumbnail.map(|image| self.resolve_image(&image)),
+            category: entry.category,
+            is_featured: entry.is_featured,
+            recurring_type: entry.recurring_type,
+            created_at: modified,
+            updated_at: modified,
+        }
+    }
+
+    /// Image names are relative to the content folder; URLs are left alone.
+    fn resolve_image(&self, image: &str) -> String {
+        if image.contains("://") {
+            image.to_string()
+        } else {
+            file_url(&self.dir.join(image))
+        }
+    }
+}
+
+/// Marks a path as a local file for the image loader, which reads nothing
+/// from disk unless it comes as a `file://` URL.
+pub fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+impl EventSource for LocalSource {
+    fn fetch_events(&self) -> BoxFuture<'_, Result<Vec<ApiEvent>>> {
+        Box::pin(LocalSource::fetch_events(self))
+    }
+}
+
+/// Yields once every time something inside one of `dirs` changes.
+pub fn watch(dirs: Vec<PathBuf>) -> impl Stream<Item = ()> {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+    let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) if !event.kind.is_access() => {
+                let _ = sender.unbounded_send(());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Content folder watch error: {}", e),
+        }
+    });
+
+    // Without a watcher the sender is dropped, which simply ends the stream
+    let watcher = match watcher {
+        Ok(mut watcher) => {
+            for dir in &dirs {
+                match watcher.watch(dir, notify::RecursiveMode::Recursive) {
+                    Ok(()) => tracing::info!("Watching content folder {}", dir.display()),
+                    Err(e) => tracing::error!("Failed to watch content folder {}: {}", dir.display(), e),
+                }
+            }
+            Some(watcher)
+        }
+        Err(e) => {
+            tracing::error!("Failed to create content folder watcher: {}", e);
+            None
+        }
+    };
+
+    futures::stream::unfold((watcher, receiver), |(watcher, mut receiver)| async move {
+        receiver.next().await?;
+
+        tokio::time::sleep(WATCH_DEBOUNCE).await;
+        while let Some(Some(())) = receiver.next().now_or_never() {}
+
+        tracing::info!("Local content changed");
+        Some(((), (watcher, receiver)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content_dir(name: &str, manifest: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("beacon-local-test-{}", std::process::id()))
+            .join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(manifest), contents).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn json_list_fills_in_defaults() {
+        let dir = content_dir(
+            "json",
+            JSON_MANIFEST,
+            r#"[
+                {"title": "Choir", "start_time": "2999-01-01T18:00:00Z", "end_time": "2999-01-01T19:00:00Z", "image": "choir.png"},
+                {"id": "old", "title": "Past", "start_time": "2000-01-01T18:00:00Z", "end_time": "2000-01-01T19:00:00Z"}
+            ]"#,
+        );
+
+        let events = LocalSource::new(dir.clone()).fetch_events().await.unwrap();
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.id, format!("local-{}-Choir", event.start_time.timestamp()));
+        assert_eq!(event.description, "");
+        assert!(!event.is_featured);
+        assert_eq!(event.image, Some(file_url(&dir.join("choir.png"))));
+    }
+
+    #[tokio::test]
+    async fn toml_table_keeps_urls_and_ids() {
+        let dir = content_dir(
+            "toml",
+            TOML_MANIFEST,
+            r#"
+                [[events]]
+                id = "bake-sale"
+                title = "Bake sale"
+                start_time = "2999-05-01T10:00:00Z"
+                end_time = "2999-05-01T12:00:00Z"
+                image = "https://example.com/cake.jpg"
+                category = "Fundraiser"
+            "#,
+        );
+
+        let events = LocalSource::new(dir).fetch_events().await.unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "bake-sale");
+        assert_eq!(events[0].category, "Fundraiser");
+        assert_eq!(events[0].image.as_deref(), Some("https://example.com/cake.jpg"));
+    }
+
+    #[tokio::test]
+    async fn missing_manifest_is_an_error() {
+        let dir = std::env::temp_dir()
+            .join(format!("beacon-local-test-{}", std::process::id()))
+            .join("empty");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(LocalSource::new(dir).fetch_events().await.is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Every piece of UI text the sign shows on its own (event content comes from the sources).
+#[derive(Debug, Clone)]
+pub struct Strings {
+    pub loading_events: String,
+    pub loading_image: String,
+    pub no_image: String,
+    pub reconnecting: String,
+    pub offline: String,
+    /// Captions under the QR codes
+    pub qr_location: String,
+    pub qr_registration: String,
+    /// Heading of agenda slides that don't set their own
+    pub agenda_title: String,
+}
+
+impl Strings {
+    fn builtin(language: &str) -> Self {
+        let (loading_events, loading_image, no_image, reconnecting, offline, qr_location, qr_registration, agenda_title) = match language {
+            "es" => (
+                "Cargando eventos...",
+                "Cargando imagen...",
+                "Imagen no disponible",
+                "Reconectando...",
+                "Sin conexión - mostrando eventos guardados",
+                "Cómo llegar",
+                "Inscríbete",
+                "Esta semana",
+            ),
+            "pt" => (
+                "Carregando eventos...",
+                "Carregando imagem...",
+                "Imagem indisponível",
+                "Reconectando...",
+                "Sem conexão - mostrando eventos salvos",
+                "Como chegar",
+                "Inscreva-se",
+                "Esta semana",
+            ),
+            _ => (
+                "Loading events...",
+                "Loading image...",
+                "No image available",
+                "Reconnecting...",
+                "Offline - showing saved events",
+                "Directions",
+                "Register",
+                "This Week",
+            ),
+        };
+
+        Self {
+            loading_events: loading_events.to_string(),
+            loading_image: loading_image.to_string(),
+            no_image: no_image.to_string(),
+            reconnecting: reconnecting.to_string(),
+            offline: offline.to_string(),
+            qr_location: qr_location.to_string(),
+            qr_registration: qr_registration.to_string(),
+            agenda_title: agenda_title.to_string(),
+        }
+    }
+
+    /// Applies `[ui_strings]` overrides from the config, keyed by field name.
+    fn with_overrides(mut self, overrides: &HashMap<String, String>) -> Self {
+        for (key, value) in overrides {
+            let slot = match key.as_str() {
+                "loading_events" => &mut self.loading_events,
+                "loading_image" => &mut self.loading_image,
+                "no_image" => &mut self.no_image,
+                "reconnecting" => &mut self.reconnecting,
+                "offline" => &mut self.offline,
+                "qr_location" => &mut self.qr_location,
+                "qr_registration" => &mut self.qr_registration,
+                "agenda_title" => &mut self.agenda_title,
+                other => {
+                    tracing::warn!("Unknown UI string '{}' in config", other);
+                    continue;
+                }
+            };
+            *slot = value.clone();
+        }
+        self
+    }
+}
+
+/// Day and month names for one language, Monday first and January first.
+struct Names {
+    days: [&'static str; 7],
+    months: [&'static str; 12],
+}
+
+const ENGLISH: Names = Names {
+    days: ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"],
+    months: [
+        "January", "February", "March", "April", "May", "June",
+        "July", "August", "September", "October", "November", "December",
+    ],
+};
+
+const SPANISH: Names = Names {
+    days: ["lunes", "martes", "miércoles", "jueves", "viernes", "sábado", "domingo"],
+    months: [
+        "enero", "febrero", "marzo", "abril", "mayo", "junio",
+        "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre",
+    ],
+};
+
+const PORTUGUESE: Names = Names {
+    days: ["segunda-feira", "terça-feira", "quarta-feira", "quinta-feira", "sexta-feira", "sábado", "domingo"],
+    months: [
+        "janeiro", "fevereiro", "março", "abril", "maio", "junho",
+        "julho", "agosto", "setembro", "outubro", "novembro", "dezembro",
+    ],
+};
+
+/// Locale, translated strings and date/time patterns for one display.
+pub struct Localization {
+    names: &'static Names,
+    date_format: String,
+    time_format: String,
+    pub strings: Strings,
+}
+
+impl Localization {
+    /// `locale` is a name such as `en`, `es`, `pt_BR` or `es-MX`; only the
+    /// language part matters. Empty or invalid formats fall back to the usual
+    /// ones for the language.
+    pub fn new(
+        locale: &str,
+        date_format: &str,
+        time_format: &str,
+        overrides: &HashMap<String, String>,
+    ) -> Self {
+        let language = locale.split(['_', '-']).next().unwrap_or_default().to_ascii_lowercase();
+        let (names, default_date, default_time) = match language.as_str() {
+            "es" => (&SPANISH, "%A, %-d de %B de %Y", "%H:%M"),
+            "pt" => (&PORTUGUESE, "%A, %-d de %B de %Y", "%H:%M"),
+            "en" => (&ENGLISH, "%A, %B %d, %Y", "%-I:%M %p"),
+            other => {
+                tracing::error!("Unsupported locale '{}', using English", other);
+                (&ENGLISH, "%A, %B %d, %Y", "%-I:%M %p")
+            }
+        };
+        let or_default = |value: &str, default: &str| {
+            if value.is_empty() {
+                return default.to_string();
+            }
+            // chrono panics when formatting with a bad pattern, so catch it here
+            if StrftimeItems::new(value).any(|item| matches!(item, Item::Error)) {
+                tracing::error!("Invalid date/time format '{}', using '{}'", value, default);
+                return default.to_string();
+            }
+            value.to_string()
+        };
+
+        Self {
+            names,
+            date_format: or_default(date_format, default_date),
+            time_format: or_default(time_format, default_time),
+            strings: Strings::builtin(&language).with_overrides(overrides),
+        }
+    }
+
+    pub fn format_date(&self, time: &DateTime<Tz>) -> String {
+        // Spanish and Portuguese day names are lowercase, but this is a heading
+        capitalize(&self.format(&self.date_format, time))
+    }
+
+    pub fn format_time(&self, time: &DateTime<Tz>) -> String {
+        self.format(&self.time_format, time)
+    }
+
+    fn day_name(&self, weekday: chrono::Weekday) -> &'static str {
+        self.names.days[weekday.num_days_from_monday() as usize]
+    }
+
+    /// chrono only knows English names, so `%A`, `%a`, `%B` and `%b` are
+    /// replaced with the translated ones before the rest is formatted.
+    fn format(&self, pattern: &str, time: &DateTime<Tz>) -> String {
+        let day = self.day_name(time.weekday());
+        let month = self.names.months[time.month0() as usize];
+
+        let mut localized = String::with_capacity(pattern.len());
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                localized.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('A') => localized.push_str(day),
+                Some('a') => localized.extend(day.chars().take(3)),
+                Some('B') => localized.push_str(month),
+                Some('b') | Some('h') => localized.extend(month.chars().take(3)),
+                Some(other) => {
+                    localized.push('%');
+                    localized.push(other);
+                }
+                None => localized.push('%'),
+            }
+        }
+
+        let mut formatted = String::new();
+        if write!(formatted, "{}", time.format(&localized)).is_err() {
+            tracing::error!("Failed to format {} with '{}'", time, pattern);
+        }
+        formatted
+    }
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sunday() -> DateTime<Tz> {
+        chrono_tz::America::New_York.with_ymd_and_hms(2025, 3, 2, 9, 5, 0).unwrap()
+    }
+
+    #[test]
+    fn translates_names() {
+        let localization = Localization::new("es_MX", "", "", &HashMap::new());
+        assert_eq!(localization.format_date(&sunday()), "Domingo, 2 de marzo de 2025");
+        assert_eq!(localization.format_time(&sunday()), "09:05");
+
+        let localization = Localization::new("pt-BR", "%a %d %b", "", &HashMap::new());
+        assert_eq!(localization.format_date(&sunday()), "Dom 02 mar");
+    }
+
+    #[test]
+    fn unknown_locale_uses_english() {
+        let localization = Localization::new("xx", "", "", &HashMap::new());
+        assert_eq!(localization.format_time(&sunday()), "9:05 AM");
+        assert_eq!(localization.strings.loading_events, "Loading events...");
+    }
+
+    #[test]
+    fn config_overrides_ui_strings() {
+        let overrides = HashMap::from([
+            (String::from("offline"), String::from("No internet")),
+            (String::from("unknown"), String::from("ignored")),
+        ]);
+        let localization = Localization::new("es", "", "", &overrides);
+        assert_eq!(localization.strings.offline, "No internet");
+        assert_eq!(localization.strings.reconnecting, "Reconectando...");
+    }
+
+    #[test]
+    fn bad_pattern_falls_back_to_default() {
+        let localization = Localization::new("en", "%A %Q", "%-I:%M %", &HashMap::new());
+        assert_eq!(localization.format_date(&sunday()), "Sunday, March 02, 2025");
+        assert_eq!(localization.format_time(&sunday()), "9:05 AM");
+    }
+}
@@ -1,8 +1,31 @@
+mod auth;
+mod cache;
+mod conditional;
 mod config;
+mod fit;
+mod health;
+mod icalendar;
+mod local;
+mod locale;
+mod pagination;
+mod playlist;
 mod pocketbase;
+mod realtime;
+mod recurrence;
+mod richtext;
+mod rotation;
+mod schedule;
+mod slide;
+mod source;
+mod timezone;
+mod transform;
+mod transition;
 
+use crate::health::{ConnectionHealth, ConnectionState, RetryAfter};
 use crate::pocketbase::ApiEvent;
-use iced::widget::{column, row, image, container, text};
+use crate::realtime::RealtimeEvent;
+use crate::slide::Slide;
+use iced::widget::{column, row, image, container, qr_code, rich_text, stack, text};
 use iced::{
     window, Element,
     Length, Settings, Subscription, Theme, Task,
@@ -11,7 +34,8 @@ use iced::executor;
 pub use iced::Program as IcedProgram;
 use iced::Color;
 use once_cell::sync::Lazy;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use iced::window::settings::PlatformSpecific;
 
 
@@ -24,8 +48,55 @@ static SETTINGS: Lazy<config::Settings> = Lazy::new(|| {
     })
 });
 
-static API_CLIENT: Lazy<pocketbase::ApiClient> = Lazy::new(|| {
-    pocketbase::ApiClient::new(SETTINGS.api_url.clone())
+static TIMEZONE: Lazy<chrono_tz::Tz> = Lazy::new(|| SETTINGS.timezone());
+
+static LOCALE: Lazy<locale::Localization> = Lazy::new(|| {
+    locale::Localization::new(
+        &SETTINGS.locale,
+        &SETTINGS.date_format,
+        &SETTINGS.time_format,
+        &SETTINGS.ui_strings,
+    )
+});
+
+static EVENT_SOURCES: Lazy<source::MergedSource> = Lazy::new(|| {
+    source::MergedSource::from_configs(
+        &SETTINGS.source_configs(),
+        *TIMEZONE,
+        SETTINGS.recurring_horizon_days,
+    )
+});
+
+static STATIC_SLIDES: Lazy<Vec<Slide>> = Lazy::new(static_slides);
+
+static PLAYLISTS: Lazy<HashMap<String, playlist::Playlist>> = Lazy::new(playlists);
+
+static IMAGE_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .expect("Failed to create HTTP client")
+});
+
+// Credentials of the sources that need them, by API origin, for images
+// served from those APIs
+static IMAGE_AUTH: Lazy<Vec<(String, std::sync::Arc<auth::Authenticator>)>> = Lazy::new(|| {
+    SETTINGS.source_configs()
+        .into_iter()
+        .filter_map(|config| match config {
+            config::SourceConfig::Pocketbase(source) => {
+                let origin = origin(&source.api_url)?;
+                let authenticator = auth::Authenticator::new(source.auth?, source.api_url);
+                Some((origin, std::sync::Arc::new(authenticator)))
+            }
+            _ => None,
+        })
+        .collect()
+});
+
+// ETag/Last-Modified per image URL, so re-downloads can be skipped with a 304
+static IMAGE_VALIDATORS: Lazy<conditional::ConditionalCache> = Lazy::new(|| {
+    conditional::ConditionalCache::on_disk(cache::EventCache::image_dir()).with_max_body_size(MAX_IMAGE_SIZE)
 });
 
 // Define some constants for styling
@@ -40,41 +111,118 @@ const DATE_COLOR: Color = Color::from_rgb(0.95, 0.85, 1.0); // Light purple tint
 const TIME_COLOR: Color = Color::from_rgb(0.8, 0.8, 0.95); // Soft purple-grey
 const LOCATION_ICON_COLOR: Color = Color::from_rgb(0.6, 0.4, 0.9); // Brighter purple
 const IMAGE_BG_COLOR: Color = Color::from_rgb(0.08, 0.08, 0.12); // Slightly lighter than background
+const ONLINE_COLOR: Color = Color::from_rgb(0.3, 0.75, 0.45); // Muted green
+const DEGRADED_COLOR: Color = Color::from_rgb(0.95, 0.7, 0.25); // Amber
+const OFFLINE_COLOR: Color = Color::from_rgb(0.9, 0.3, 0.3); // Soft red
+const DESCRIPTION_TEXT_SIZE: f32 = 44.0;
+const HEADING_TEXT_SIZE: f32 = 52.0;
+const PAGE_INDICATOR_SIZE: f32 = 24.0;
+const ANNOUNCEMENT_TITLE_SIZE: f32 = 80.0;
+const WELCOME_TITLE_SIZE: f32 = 120.0;
+const CLOCK_TEXT_SIZE: f32 = 200.0;
+const AGENDA_DAY_SIZE: f32 = 44.0;
+const AGENDA_TITLE_SIZE: f32 = 40.0;
+const AGENDA_DETAIL_SIZE: f32 = 30.0;
+const AGENDA_THUMBNAIL_WIDTH: f32 = 160.0;
+const AGENDA_THUMBNAIL_HEIGHT: f32 = 100.0;
+const AGENDA_SPACING: f32 = 20.0; // Between the headings and entries of an agenda
+const AGENDA_SECTION_SPACING: f32 = 40.0; // Between an agenda's title, list and page indicator
+const AGENDA_THUMBNAIL_GAP: f32 = 30.0; // Between an entry's thumbnail and text
+const AGENDA_LINE_SPACING: f32 = 8.0; // Between an entry's title and details
+const SLIDE_PADDING: f32 = 60.0; // Around a slide's content
+const IMAGE_WIDTH: f32 = 900.0; // The event image, and the title above it
+const COLUMN_SPACING: f32 = 60.0; // Between an event's image and details
+const DETAILS_SPACING: f32 = 30.0; // Between the category, date, location and description
+const DETAIL_PADDING: f32 = 20.0; // Around the title, date and location
+const BADGE_PADDING: f32 = 12.0;
+const DESCRIPTION_PADDING: f32 = 25.0;
+const LIST_INDENT: f32 = 24.0;
+const DESCRIPTION_SPACING: f32 = 8.0; // Between the lines of a description
+const LINE_HEIGHT: f32 = 1.3; // iced's default, in ems
 const LOADING_FRAMES: [&str; 4] = ["⠋", "⠙", "⠹", "⠸"];
 const MAX_IMAGE_SIZE: u64 = 2 * 1024 * 1024; // 2MB limit
+const REALTIME_RESYNC_INTERVAL: Duration = Duration::from_secs(60 * 60); // Drop finished events while streaming
 
 #[derive(Debug)]
 struct DigitalSign {
     events: Vec<Event>,
-    current_event_index: usize,
+    // The events plus the slides from the config, in the order the rotation
+    // sees them; with a playlist, exactly what it plays
+    slides: Vec<Slide>,
+    playlist: Option<&'static playlist::Playlist>,
+    // Event categories to show; empty means all of them
+    categories: Vec<String>,
+    // Index of the `[[schedule]]` rule in effect
+    schedule_rule: Option<usize>,
+    // The local date the slides were last built on; agendas count from it
+    slides_date: chrono::NaiveDate,
+    current_slide_index: usize,
     last_update: Instant,
     last_refresh: Instant,
     loaded_images: std::collections::HashMap<String, image::Handle>,
     loading_frame: usize,
     is_fetching: bool,
+    realtime_connected: bool,
+    health: ConnectionHealth,
+    rotation: Box<dyn rotation::Strategy>,
+    // Page of the current slide's text, for slides too long to show at once
+    current_page: usize,
+    // Generated once per URL and kept while an event links to it
+    qr_codes: HashMap<String, qr_code::Data>,
+    // The slide being animated away, while a transition runs
+    transition: Option<transition::Transition<Outgoing>>,
 }
 
-#[derive(Debug, Clone)]
+/// What was on screen when a transition started: a slide page, or the loading message.
+#[derive(Debug)]
+struct Outgoing {
+    slide: Option<Slide>,
+    page: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 struct Event {
+    id: String,
     title: String,
-    description: String,
+    // Title, category and location sized to fit their space on screen
+    title_text: fit::Fitted,
+    category_text: fit::Fitted,
+    location_text: fit::Fitted,
+    // The formatted description split to fit the description box, shown one page at a time
+    description_pages: Vec<Vec<richtext::Line>>,
     start_time: String,
     end_time: String,
     date: String,
     location: String,
-    //location_url: Option<String>,
+    location_url: Option<String>,
+    registration_url: Option<String>,
     image_url: Option<String>,
     category: String,
-    //is_featured: bool,
+    is_featured: bool,
     timestamp: chrono::DateTime<chrono::Utc>,
+    end_timestamp: chrono::DateTime<chrono::Utc>,
+    // Set by a playlist entry; takes precedence over every other duration
+    duration: Option<Duration>,
+}
+
+/// Some sources failed during a refresh that still produced events; those
+/// sources are showing their earlier events.
+#[derive(Debug, Clone)]
+struct PartialFailure {
+    error: String,
+    retry_after: Option<RetryAfter>,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     Tick,
-    EventsLoaded(Vec<Event>),
-    Error(String),
+    Frame,
+    EventsLoaded(Vec<Event>, Option<PartialFailure>),
+    CachedEventsLoaded(Vec<Event>),
+    FetchFailed(String, Option<RetryAfter>),
     ImageLoaded(String, image::Handle),
+    Realtime(RealtimeEvent),
+    LocalContentChanged,
 }
 
 impl IcedProgram for DigitalSign {
@@ -93,6 +241,8 @@ impl IcedProgram for DigitalSign {
             Message::Tick => {
                 let mut tasks = vec![];
                 state.loading_frame = (state.loading_frame + 1) % LOADING_FRAMES.len();
+                state.expire_finished_events();
+                state.follow_schedule();
 
                 if state.should_refresh() && !state.is_fetching {
                     tracing::info!("Refresh needed, starting event fetch");
@@ -100,21 +250,28 @@ impl IcedProgram for DigitalSign {
                     tasks.push(Task::perform(fetch_events(), Message::handle_result));
                 }
 
-                if !state.events.is_empty() 
-                    && Instant::now().duration_since(state.last_update) >= SETTINGS.slide_interval() 
-                {
-                    let next_index = (state.current_event_index + 1) % state.events.len();
-                    tracing::info!("Updating current event index from {} to {}", 
-                        state.current_event_index,
+                let slide_done = Instant::now().duration_since(state.last_update) >= state.current_slide_duration();
+                let next_index = if slide_done && !state.advance_page() {
+                    let slides: Vec<rotation::SlideInfo> = state.slides
+                        .iter()
+                        .map(Slide::slide_info)
+                        .collect();
+                    let current = state.slides.get(state.current_slide_index).map(Slide::id);
+                    state.rotation.next(&slides, current)
+                } else {
+                    None
+                };
+
+                if let Some(next_index) = next_index {
+                    tracing::info!("Updating current slide index from {} to {}", 
+                        state.current_slide_index,
                         next_index
                     );
 
                     // Clear all images that aren't needed anymore
                     let mut urls_to_remove = Vec::new();
                     for url in state.loaded_images.keys() {
-                        let is_needed = state.events.iter().any(|e| {
-                            e.image_url.as_ref().map_or(false, |event_url| event_url == url)
-                        });
+                        let is_needed = state.slides.iter().any(|slide| slide.image_urls().contains(&url.as_str()));
                         if !is_needed {
                             urls_to_remove.push(url.clone());
                         }
@@ -125,21 +282,27 @@ impl IcedProgram for DigitalSign {
                     }
 
                     // Update current index and load new image if needed
-                    state.current_event_index = next_index;
+                    // A playlist may repeat a slide back to back; that needs no animation
+                    let outgoing = state.slides.get(state.current_slide_index).cloned();
+                    if outgoing.as_ref().map(Slide::id) != state.slides.get(next_index).map(Slide::id) {
+                        state.start_transition(outgoing, state.current_page);
+                    }
+                    state.current_slide_index = next_index;
+                    state.current_page = 0;
                     state.last_update = Instant::now();
 
-                    if let Some(current_event) = state.events.get(state.current_event_index) {
-                        if let Some(url) = &current_event.image_url {
-                            let url_clone = url.clone();
+                    if let Some(current_slide) = state.slides.get(state.current_slide_index) {
+                        for url in current_slide.image_urls() {
+                            let url_clone = url.to_string();
                             if !state.loaded_images.contains_key(&url_clone) {
-                                tracing::info!("Starting image load for new current event: {}", url_clone);
+                                tracing::info!("Starting image load for new current slide: {}", url_clone);
                                 let url_for_closure = url_clone.clone();
                                 tasks.push(Task::perform(
                                     load_image(url_clone),
                                     move |handle| Message::ImageLoaded(url_for_closure.clone(), handle)
                                 ));
                             } else {
-                                tracing::info!("Image already loaded for current event: {}", url_clone);
+                                tracing::info!("Image already loaded for current slide: {}", url_clone);
                             }
                         }
                     }
@@ -152,68 +315,55 @@ impl IcedProgram for DigitalSign {
                     Task::batch(tasks)
                 }
             }
-            Message::EventsLoaded(events) => {
-                tracing::info!("Events loaded: {} events", events.len());
-                
-                // Clear all existing images as we have a new set of events
-                state.loaded_images.clear();
-                tracing::info!("Cleared all existing images");
-                
-                state.events = events;
-                
-                // Reset current event index if needed
-                if state.current_event_index >= state.events.len() && !state.events.is_empty() {
-                    tracing::info!("Resetting current event index from {} to 0", state.current_event_index);
-                    state.current_event_index = 0;
+            Message::Frame => {
+                if state.transition.as_ref().map_or(false, |t| t.is_finished()) {
+                    state.transition = None;
                 }
-                
+                Task::none()
+            }
+            Message::EventsLoaded(events, failure) => {
+                tracing::info!("Events loaded: {} events", events.len());
                 state.last_refresh = Instant::now();
                 state.is_fetching = false;
-                
-                // Load all images in parallel
-                let mut image_tasks = Vec::new();
-                
-                // First, add the current event's image if it exists
-                if let Some(event) = state.events.get(state.current_event_index) {
-                    if let Some(url) = &event.image_url {
-                        tracing::info!("Starting immediate load for current image: {}", url);
-                        let url_clone = url.clone();
-                        image_tasks.push(Task::perform(
-                            load_image(url_clone.clone()),
-                            move |handle| Message::ImageLoaded(url_clone.clone(), handle)
-                        ));
-                    }
-                }
-                
-                // Then queue the rest of the images
-                for (index, event) in state.events.iter().enumerate() {
-                    if index != state.current_event_index {
-                        if let Some(url) = &event.image_url {
-                            tracing::info!("Queueing image preload for: {}", url);
-                            let url_clone = url.clone();
-                            image_tasks.push(Task::perform(
-                                load_image(url_clone.clone()),
-                                move |handle| Message::ImageLoaded(url_clone.clone(), handle)
-                            ));
-                        }
+                match failure {
+                    None => state.health.record_success(),
+                    Some(failure) => {
+                        tracing::error!("Error: {}", failure.error);
+                        state.health.record_failure(failure.retry_after);
                     }
                 }
-
-                if !image_tasks.is_empty() {
-                    tracing::info!("Starting load of {} images", image_tasks.len());
-                    Task::batch(image_tasks)
-                } else {
-                    Task::none()
-                }
+                state.apply_events(events)
+            }
+            Message::CachedEventsLoaded(events) => {
+                tracing::info!("Cached events loaded: {} events", events.len());
+                state.apply_events(events)
             }
             Message::ImageLoaded(url, handle) => {
                 tracing::info!("Image loaded: {}", url);
                 state.loaded_images.insert(url, handle);
                 Task::none()
             }
-            Message::Error(error) => {
+            Message::Realtime(event) => state.apply_realtime(event),
+            Message::LocalContentChanged => {
+                if state.is_fetching {
+                    Task::none()
+                } else {
+                    tracing::info!("Reloading events after local content change");
+                    state.is_fetching = true;
+                    Task::perform(fetch_events(), Message::handle_result)
+                }
+            }
+            Message::FetchFailed(error, retry_after) => {
                 tracing::error!("Error: {}", error);
                 state.is_fetching = false;
+                state.health.record_failure(retry_after);
+
+                // Nothing on screen yet, so fall back to the last good event list
+                if state.events.is_empty() {
+                    if let Some(events) = load_cached_events() {
+                        return Task::done(Message::CachedEventsLoaded(events));
+                    }
+                }
                 Task::none()
             }
         }
@@ -224,200 +374,767 @@ impl IcedProgram for DigitalSign {
         state: &'a Self::State,
         _window_id: window::Id,
     ) -> Element<'a, Message, Theme, Self::Renderer> {
-        let content: Element<'a, Message, Theme, Self::Renderer> = if let Some(event) = state.events.get(state.current_event_index) {
-            let mut main_column = column![].spacing(40).padding(60).width(Length::Fill);
+        let mut layers = stack![];
+        match &state.transition {
+            Some(transition) => {
+                let (outgoing, incoming) = transition.layers();
+                let from = &transition.from;
+                for (slide, page, layer) in [
+                    (from.slide.as_ref(), from.page, outgoing),
+                    (state.slides.get(state.current_slide_index), state.current_page, incoming),
+                ] {
+                    if layer.alpha <= 0.0 {
+                        continue;
+                    }
+                    let view = slide_view(state, slide, page, layer.alpha);
+                    layers = layers.push(if layer.is_untransformed() {
+                        view
+                    } else {
+                        transform::Transform::new(view, layer.offset, layer.scale).into()
+                    });
+                }
+            }
+            None => {
+                layers = layers.push(slide_view(
+                    state,
+                    state.slides.get(state.current_slide_index),
+                    state.current_page,
+                    1.0,
+                ));
+            }
+        }
 
-            // Left column with title and image
-            let left_column = column![
-                // Title with dynamic size and enhanced color
-                container(
-                    text(&event.title)
-                        .size(if event.title.len() > 50 { 72 } else { 88 })
-                        .style(|_: &Theme| text::Style { color: Some(TITLE_COLOR), ..Default::default() })
-                )
-                .width(Length::Fill)
-                .padding(20),
+        container(layers.push(connection_indicator(state.health.state())))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(|_: &Theme| container::Style {
+                background: Some(BACKGROUND_COLOR.into()),
+                ..Default::default()
+            })
+            .into()
+    }
 
-                // Image container with enhanced styling
-                container(
-                    if let Some(ref image_url) = event.image_url {
-                        if let Some(handle) = state.loaded_images.get(image_url) {
-                            container(
-                                image::Image::new(handle.clone())
-                                    .width(Length::Fixed(900.0))
-                                    .height(Length::Fixed(600.0))
-                            )
-                            .style(|_: &Theme| container::Style {
-                                background: Some(IMAGE_BG_COLOR.into()),
-                                ..Default::default()
-                            })
-                        } else {
-                            container(
-                                column![
-                                    text(LOADING_FRAMES[state.loading_frame])
-                                        .size(80)
-                                        .style(|_: &Theme| text::Style { color: Some(ACCENT_COLOR), ..Default::default() }),
-                                    text("Loading image...")
-                                        .size(40)
-                                        .style(|_: &Theme| text::Style { color: Some(SECONDARY_TEXT_COLOR), ..Default::default() })
-                                ]
-                                .spacing(20)
-                                .align_x(iced::alignment::Horizontal::Center)
-                            )
-                        }
-                    } else {
-                        container(
-                            text("No image available")
-                                .size(32)
-                                .style(|_: &Theme| text::Style { color: Some(SECONDARY_TEXT_COLOR), ..Default::default() })
-                        )
-                    }
-                )
-                .width(Length::Fixed(900.0))
-                .height(Length::Fixed(600.0))
-                .style(|_: &Theme| container::Style {
-                    background: Some(IMAGE_BG_COLOR.into()),
-                    ..Default::default()
-                })
-            ]
-            .spacing(20);
+    fn subscription(&self, state: &Self::State) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            iced::time::every(Duration::from_millis(100))
+                .map(|_| Message::Tick)
+        ];
 
-            // Right column with category, date/time, location, and description
-            let right_column = column![
-                // Category badge with gradient-like effect
-                container(
-                    text(event.category.to_uppercase())
-                        .size(36)
-                        .style(|_: &Theme| text::Style { color: Some(TEXT_COLOR), ..Default::default() })
-                )
-                .padding(12)
-                .style(|_: &Theme| container::Style {
-                    background: Some(CATEGORY_COLOR.into()),
-                    ..Default::default()
-                }),
+        // Redraw every frame, but only while something is moving
+        if state.transition.is_some() {
+            subscriptions.push(window::frames().map(|_| Message::Frame));
+        }
 
-                // Date and time with enhanced colors
-                container(
-                    column![
-                        text(&event.date)
-                            .size(64)
-                            .style(|_: &Theme| text::Style { color: Some(DATE_COLOR), ..Default::default() }),
-                        text(format!("{} - {}", event.start_time, event.end_time))
-                            .size(56)
-                            .style(|_: &Theme| text::Style { color: Some(TIME_COLOR), ..Default::default() })
-                    ]
-                    .spacing(15)
-                )
-                .padding(20),
+        // `Subscription::run` only calls these builders when the subscription first starts,
+        // so the HTTP client and the folder watcher are not recreated on every update
+        if SETTINGS.realtime_enabled {
+            subscriptions.push(Subscription::run(realtime_events).map(Message::Realtime));
+        }
+
+        if !SETTINGS.local_content_dirs().is_empty() {
+            subscriptions.push(
+                Subscription::run(local_content_changes).map(|_| Message::LocalContentChanged)
+            );
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn theme(&self, _state: &Self::State, _window_id: window::Id) -> Theme {
+        Theme::Dark
+    }
+}
 
-                // Location with colored icon
-                if !event.location.is_empty() {
+/// Everything on screen for one slide, or the loading message when there is
+/// nothing to show yet. `alpha` fades the whole slide for transitions.
+fn slide_view<'a>(state: &'a DigitalSign, slide: Option<&'a Slide>, page: usize, alpha: f32) -> Element<'a, Message> {
+    match slide {
+        None => loading_view(alpha),
+        Some(Slide::Event(event)) => {
+            let mut layers = stack![event_view(state, event, page, alpha)];
+            if let Some(codes) = qr_codes_view(event, &state.qr_codes, alpha) {
+                layers = layers.push(codes);
+            }
+            layers.into()
+        }
+        Some(Slide::Announcement(announcement)) => announcement_view(announcement, page, alpha),
+        Some(Slide::Image(image_slide)) => image_slide_view(state, image_slide, alpha),
+        Some(Slide::Welcome(welcome)) => welcome_view(welcome, alpha),
+        Some(Slide::Widget(widget)) => widget_view(widget, alpha),
+        Some(Slide::Agenda(agenda)) => agenda_view(state, agenda, page, alpha),
+    }
+}
+
+fn event_view<'a>(state: &'a DigitalSign, event: &'a Event, page: usize, alpha: f32) -> Element<'a, Message> {
+    let mut main_column = column![].spacing(40).padding(SLIDE_PADDING).width(Length::Fill);
+
+    // Left column with title and image
+    let left_column = column![
+        // Title with dynamic size and enhanced color
+        container(
+            text(&event.title_text.text)
+                .size(event.title_text.size)
+                .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() })
+        )
+        .width(Length::Fill)
+        .padding(DETAIL_PADDING),
+
+        // Image container with enhanced styling
+        container(
+            if let Some(ref image_url) = event.image_url {
+                if let Some(handle) = state.loaded_images.get(image_url) {
                     container(
-                        row![
-                            text("⌾")  // Location/target symbol
-                                .size(48)
-                                .font(iced::Font::with_name("Segoe UI Symbol"))
-                                .style(|_: &Theme| text::Style { color: Some(LOCATION_ICON_COLOR), ..Default::default() }),
-                            text(&event.location)
-                                .size(48)
-                                .style(|_: &Theme| text::Style { color: Some(SECONDARY_TEXT_COLOR), ..Default::default() })
-                        ]
-                        .spacing(15)
-                        .align_y(iced::Alignment::Center)
+                        image::Image::new(handle.clone())
+                            .width(Length::Fixed(IMAGE_WIDTH))
+                            .height(Length::Fixed(600.0))
+                            .opacity(alpha)
                     )
-                    .padding(20)
+                    .style(move |_: &Theme| container::Style {
+                        background: Some(fade(IMAGE_BG_COLOR, alpha).into()),
+                        ..Default::default()
+                    })
                 } else {
-                    container(text(""))
-                },
-
-                // Description with styled background
+                    container(
+                        column![
+                            text(LOADING_FRAMES[state.loading_frame])
+                                .size(80)
+                                .style(move |_: &Theme| text::Style { color: Some(fade(ACCENT_COLOR, alpha)), ..Default::default() }),
+                            text(&LOCALE.strings.loading_image)
+                                .size(40)
+                                .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
+                        ]
+                        .spacing(20)
+                        .align_x(iced::alignment::Horizontal::Center)
+                    )
+                }
+            } else {
                 container(
-                    text(&event.description)
-                        .size(44)
-                        .style(|_: &Theme| text::Style { color: Some(TEXT_COLOR), ..Default::default() })
+                    text(&LOCALE.strings.no_image)
+                        .size(32)
+                        .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
                 )
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .padding(25)
-                .style(|_: &Theme| container::Style {
-                    background: Some(DESCRIPTION_BG_COLOR.into()),
-                    ..Default::default()
-                })
+            }
+        )
+        .width(Length::Fixed(IMAGE_WIDTH))
+        .height(Length::Fixed(600.0))
+        .style(move |_: &Theme| container::Style {
+            background: Some(fade(IMAGE_BG_COLOR, alpha).into()),
+            ..Default::default()
+        })
+    ]
+    .spacing(20);
+
+    // Right column with category, date/time, location, and description
+    let right_column = column![
+        // Category badge with gradient-like effect
+        container(
+            text(&event.category_text.text)
+                .size(event.category_text.size)
+                .style(move |_: &Theme| text::Style { color: Some(fade(TEXT_COLOR, alpha)), ..Default::default() })
+        )
+        .padding(BADGE_PADDING)
+        .style(move |_: &Theme| container::Style {
+            background: Some(fade(CATEGORY_COLOR, alpha).into()),
+            ..Default::default()
+        }),
+
+        // Date and time with enhanced colors
+        container(
+            column![
+                text(&event.date)
+                    .size(64)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(DATE_COLOR, alpha)), ..Default::default() }),
+                text(format!("{} - {}", event.start_time, event.end_time))
+                    .size(56)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(TIME_COLOR, alpha)), ..Default::default() })
             ]
-            .spacing(30)
-            .width(Length::Fill)
-            .height(Length::Fill);
+            .spacing(15)
+        )
+        .padding(DETAIL_PADDING),
+
+        // Location with colored icon
+        if !event.location_text.text.is_empty() {
+            container(
+                row![
+                    text("⌾")  // Location/target symbol
+                        .size(48)
+                        .font(iced::Font::with_name("Segoe UI Symbol"))
+                        .style(move |_: &Theme| text::Style { color: Some(fade(LOCATION_ICON_COLOR, alpha)), ..Default::default() }),
+                    text(&event.location_text.text)
+                        .size(event.location_text.size)
+                        .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
+                ]
+                .spacing(15)
+                .align_y(iced::Alignment::Center)
+            )
+            .padding(DETAIL_PADDING)
+        } else {
+            container(text(""))
+        },
 
-            // Main content row
-            let content_row = row![
-                left_column,
-                right_column
+        // Description with styled background, one page at a time when it's long
+        container({
+            let pages = &event.description_pages;
+            let page = page.min(pages.len().saturating_sub(1));
+            let mut description = column![
+                container(description_view(pages.get(page).map_or(&[], Vec::as_slice), alpha))
+                    .height(Length::Fill)
             ]
-            .spacing(60)
-            .height(Length::Fill);
+            .spacing(10);
 
-            main_column = main_column.push(content_row);
+            if pages.len() > 1 {
+                description = description.push(
+                    container(
+                        text(format!("{} / {}", page + 1, pages.len()))
+                            .size(PAGE_INDICATOR_SIZE)
+                            .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
+                    )
+                    .width(Length::Fill)
+                    .align_x(iced::alignment::Horizontal::Right)
+                );
+            }
+            description
+        })
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(DESCRIPTION_PADDING)
+        .style(move |_: &Theme| container::Style {
+            background: Some(fade(DESCRIPTION_BG_COLOR, alpha).into()),
+            ..Default::default()
+        })
+    ]
+    .spacing(DETAILS_SPACING)
+    .width(Length::Fill)
+    .height(Length::Fill);
 
-            container(main_column)
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .center_y(Length::Fill)
-                .into()
-        } else {
+    // Main content row
+    let content_row = row![
+        left_column,
+        right_column
+    ]
+    .spacing(COLUMN_SPACING)
+    .height(Length::Fill);
+
+    main_column = main_column.push(content_row);
+
+    container(main_column)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_y(Length::Fill)
+        .into()
+}
+
+fn loading_view<'a>(alpha: f32) -> Element<'a, Message> {
+    container(
+        text(&LOCALE.strings.loading_events)
+            .size(64)
+            .style(move |_: &Theme| text::Style { color: Some(fade(ACCENT_COLOR, alpha)), ..Default::default() })
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .into()
+}
+
+/// A heading over formatted text that uses the whole screen.
+fn announcement_view<'a>(announcement: &'a slide::Announcement, page: usize, alpha: f32) -> Element<'a, Message> {
+    let pages = &announcement.pages;
+    let page = page.min(pages.len().saturating_sub(1));
+
+    let mut content = column![
+        text(&announcement.title)
+            .size(ANNOUNCEMENT_TITLE_SIZE)
+            .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() }),
+        container(description_view(pages.get(page).map_or(&[], Vec::as_slice), alpha))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(25)
+            .style(move |_: &Theme| container::Style {
+                background: Some(fade(DESCRIPTION_BG_COLOR, alpha).into()),
+                ..Default::default()
+            })
+    ]
+    .spacing(40);
+
+    if pages.len() > 1 {
+        content = content.push(
             container(
-                text("Loading events...")
-                    .size(64)
-                    .style(|_: &Theme| text::Style { color: Some(ACCENT_COLOR), ..Default::default() })
+                text(format!("{} / {}", page + 1, pages.len()))
+                    .size(PAGE_INDICATOR_SIZE)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
             )
             .width(Length::Fill)
-            .height(Length::Fill)
-            .center_x(Length::Fill)
-            .center_y(Length::Fill)
-            .into()
-        };
+            .align_x(iced::alignment::Horizontal::Right)
+        );
+    }
+
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(60)
+        .into()
+}
 
-        container(content)
+/// A picture over the whole screen, with an optional caption along the bottom.
+fn image_slide_view<'a>(state: &'a DigitalSign, image_slide: &'a slide::ImageSlide, alpha: f32) -> Element<'a, Message> {
+    let picture: Element<'a, Message> = match state.loaded_images.get(&image_slide.url) {
+        Some(handle) => image::Image::new(handle.clone())
             .width(Length::Fill)
             .height(Length::Fill)
-            .style(|_: &Theme| container::Style {
-                background: Some(BACKGROUND_COLOR.into()),
+            .content_fit(match image_slide.fit {
+                config::ImageFit::Contain => iced::ContentFit::Contain,
+                config::ImageFit::Cover => iced::ContentFit::Cover,
+            })
+            .opacity(alpha)
+            .into(),
+        None => container(
+            text(LOADING_FRAMES[state.loading_frame])
+                .size(80)
+                .style(move |_: &Theme| text::Style { color: Some(fade(ACCENT_COLOR, alpha)), ..Default::default() })
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .into(),
+    };
+
+    let Some(caption) = &image_slide.caption else {
+        return picture;
+    };
+
+    stack![
+        picture,
+        container(
+            container(
+                text(caption)
+                    .size(48)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(TEXT_COLOR, alpha)), ..Default::default() })
+            )
+            .width(Length::Fill)
+            .padding(30)
+            .style(move |_: &Theme| container::Style {
+                background: Some(fade(Color { a: 0.7, ..BACKGROUND_COLOR }, alpha).into()),
                 ..Default::default()
             })
-            .into()
+        )
+        .height(Length::Fill)
+        .align_y(iced::alignment::Vertical::Bottom)
+    ]
+    .into()
+}
+
+/// Large centred text, e.g. a greeting for visitors.
+fn welcome_view<'a>(welcome: &'a slide::Welcome, alpha: f32) -> Element<'a, Message> {
+    let mut content = column![
+        text(&welcome.title)
+            .size(WELCOME_TITLE_SIZE)
+            .align_x(iced::alignment::Horizontal::Center)
+            .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() })
+    ]
+    .spacing(40)
+    .align_x(iced::alignment::Horizontal::Center);
+
+    if !welcome.subtitle.is_empty() {
+        content = content.push(
+            text(&welcome.subtitle)
+                .size(64)
+                .align_x(iced::alignment::Horizontal::Center)
+                .style(move |_: &Theme| text::Style { color: Some(fade(DATE_COLOR, alpha)), ..Default::default() })
+        );
     }
 
-    fn subscription(&self, _state: &Self::State) -> Subscription<Message> {
-        iced::time::every(std::time::Duration::from_millis(100))
-            .map(|_| Message::Tick)
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(60)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .into()
+}
+
+/// Live content drawn by the sign; redrawn on every tick.
+fn widget_view<'a>(widget: &'a slide::Widget, alpha: f32) -> Element<'a, Message> {
+    let content = match widget.kind {
+        config::WidgetKind::Clock => {
+            let now = chrono::Utc::now().with_timezone(&*TIMEZONE);
+            column![
+                text(LOCALE.format_time(&now))
+                    .size(CLOCK_TEXT_SIZE)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() }),
+                text(LOCALE.format_date(&now))
+                    .size(64)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(DATE_COLOR, alpha)), ..Default::default() })
+            ]
+            .spacing(20)
+            .align_x(iced::alignment::Horizontal::Center)
+        }
+    };
+
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .into()
+}
+
+/// One page of upcoming events under their dates, each with a thumbnail,
+/// the time, title and location.
+fn agenda_view<'a>(state: &'a DigitalSign, agenda: &'a slide::Agenda, page: usize, alpha: f32) -> Element<'a, Message> {
+    let pages = &agenda.pages;
+    let page = page.min(pages.len().saturating_sub(1));
+
+    let mut list = column![].spacing(AGENDA_SPACING);
+    for day in pages.get(page).into_iter().flatten() {
+        list = list.push(
+            text(&day.heading)
+                .size(AGENDA_DAY_SIZE)
+                .style(move |_: &Theme| text::Style { color: Some(fade(DATE_COLOR, alpha)), ..Default::default() })
+        );
+
+        for entry in &day.entries {
+            let thumbnail: Element<'a, Message> = match entry.event.image_url.as_ref().and_then(|url| state.loaded_images.get(url)) {
+                Some(handle) => image::Image::new(handle.clone())
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .content_fit(iced::ContentFit::Cover)
+                    .opacity(alpha)
+                    .into(),
+                None => text("").into(),
+            };
+
+            list = list.push(
+                row![
+                    container(thumbnail)
+                        .width(Length::Fixed(AGENDA_THUMBNAIL_WIDTH))
+                        .height(Length::Fixed(AGENDA_THUMBNAIL_HEIGHT))
+                        .style(move |_: &Theme| container::Style {
+                            background: Some(fade(IMAGE_BG_COLOR, alpha).into()),
+                            ..Default::default()
+                        }),
+                    column![
+                        text(&entry.title.text)
+                            .size(entry.title.size)
+                            .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() }),
+                        text(&entry.details.text)
+                            .size(entry.details.size)
+                            .style(move |_: &Theme| text::Style { color: Some(fade(TIME_COLOR, alpha)), ..Default::default() })
+                    ]
+                    .spacing(AGENDA_LINE_SPACING)
+                ]
+                .spacing(AGENDA_THUMBNAIL_GAP)
+                .align_y(iced::Alignment::Center)
+            );
+        }
     }
 
-    fn theme(&self, _state: &Self::State, _window_id: window::Id) -> Theme {
-        Theme::Dark
+    let mut content = column![
+        text(&agenda.title)
+            .size(ANNOUNCEMENT_TITLE_SIZE)
+            .style(move |_: &Theme| text::Style { color: Some(fade(TITLE_COLOR, alpha)), ..Default::default() }),
+        container(list)
+            .width(Length::Fill)
+            .height(Length::Fill)
+    ]
+    .spacing(AGENDA_SECTION_SPACING);
+
+    if pages.len() > 1 {
+        content = content.push(
+            container(
+                text(format!("{} / {}", page + 1, pages.len()))
+                    .size(PAGE_INDICATOR_SIZE)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(SECONDARY_TEXT_COLOR, alpha)), ..Default::default() })
+            )
+            .width(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Right)
+        );
+    }
+
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(SLIDE_PADDING)
+        .into()
+}
+
+/// Sizes in `agenda_view`'s list. Assumes a one-line title and always
+/// leaves room for the page indicator.
+fn agenda_layout() -> slide::AgendaLayout {
+    let line = |size: f32| size * LINE_HEIGHT;
+    // Beside the thumbnail
+    let width = SETTINGS.window_width as f32 - 2.0 * SLIDE_PADDING - AGENDA_THUMBNAIL_WIDTH - AGENDA_THUMBNAIL_GAP;
+
+    slide::AgendaLayout {
+        height: SETTINGS.window_height as f32
+            - 2.0 * SLIDE_PADDING
+            - (line(ANNOUNCEMENT_TITLE_SIZE) + AGENDA_SECTION_SPACING)
+            - (line(PAGE_INDICATOR_SIZE) + AGENDA_SECTION_SPACING),
+        heading: line(AGENDA_DAY_SIZE),
+        // Title and details are fitted to one line each
+        entry: AGENDA_THUMBNAIL_HEIGHT.max(line(AGENDA_TITLE_SIZE) + AGENDA_LINE_SPACING + line(AGENDA_DETAIL_SIZE)),
+        spacing: AGENDA_SPACING,
+        title: fit::Area {
+            width,
+            max_lines: 1,
+            min_size: 28.0,
+            max_size: AGENDA_TITLE_SIZE,
+            font: iced::Font::DEFAULT,
+        },
+        details: fit::Area {
+            width,
+            max_lines: 1,
+            min_size: 22.0,
+            max_size: AGENDA_DETAIL_SIZE,
+            font: iced::Font::DEFAULT,
+        },
     }
 }
 
+/// `color` with its opacity scaled by `alpha`.
+fn fade(color: Color, alpha: f32) -> Color {
+    Color { a: color.a * alpha, ..color }
+}
+
+/// One page of a formatted description: headings, paragraphs and list
+/// items, with bold, italic, link and code styling on the text.
+fn description_view<'a>(lines: &'a [richtext::Line], alpha: f32) -> Element<'a, Message> {
+    let mut description = column![].spacing(DESCRIPTION_SPACING);
+
+    for line in lines {
+        let spans = line_spans(line, alpha);
+        description = description.push(match line.kind {
+            // Half a line between paragraphs
+            richtext::LineKind::Blank => Element::from(text("").size(DESCRIPTION_TEXT_SIZE / 2.0)),
+            richtext::LineKind::ListItem => container(rich_text(spans))
+                .padding(iced::Padding { left: LIST_INDENT, ..iced::Padding::ZERO })
+                .into(),
+            _ => rich_text(spans).into(),
+        });
+    }
+
+    description.into()
+}
+
+fn line_size(kind: richtext::LineKind) -> f32 {
+    match kind {
+        richtext::LineKind::Heading => HEADING_TEXT_SIZE,
+        _ => DESCRIPTION_TEXT_SIZE,
+    }
+}
+
+/// The styled spans of one line of a description.
+fn line_spans(line: &richtext::Line, alpha: f32) -> Vec<text::Span<'_, (), iced::Font>> {
+    line.spans
+        .iter()
+        .map(|span| {
+            let mut font = if span.code { iced::Font::MONOSPACE } else { iced::Font::DEFAULT };
+            if span.strong || line.kind == richtext::LineKind::Heading {
+                font.weight = iced::font::Weight::Bold;
+            }
+            if span.emphasis {
+                font.style = iced::font::Style::Italic;
+            }
+            let color = match line.kind {
+                richtext::LineKind::Heading => DATE_COLOR,
+                _ if span.link => ACCENT_COLOR,
+                _ => TEXT_COLOR,
+            };
+
+            text::Span::new(span.text.as_str())
+                .size(line_size(line.kind))
+                .font(font)
+                .color(fade(color, alpha))
+                .underline(span.link)
+        })
+        .collect()
+}
+
+/// Height of one line of a description wrapped to `width`, as
+/// `description_view` draws it.
+fn line_height(line: &richtext::Line, width: f32) -> f32 {
+    match line.kind {
+        richtext::LineKind::Blank => DESCRIPTION_TEXT_SIZE / 2.0 * LINE_HEIGHT,
+        richtext::LineKind::ListItem => fit::height(&line_spans(line, 1.0), DESCRIPTION_TEXT_SIZE, width - LIST_INDENT),
+        kind => fit::height(&line_spans(line, 1.0), line_size(kind), width),
+    }
+}
+
+/// QR codes for the event's map link and registration page, each with a
+/// caption, in the configured corner of the image column so they never
+/// cover the details or description. `None` when the event has neither.
+fn qr_codes_view<'a>(
+    event: &Event,
+    codes: &'a HashMap<String, qr_code::Data>,
+    alpha: f32,
+) -> Option<Element<'a, Message>> {
+    let links = [
+        (event.location_url.as_ref(), &LOCALE.strings.qr_location),
+        (event.registration_url.as_ref(), &LOCALE.strings.qr_registration),
+    ];
+
+    let mut codes_row = row![].spacing(24);
+    let mut any = false;
+    for (url, caption) in links {
+        let Some(data) = url.and_then(|url| codes.get(url)) else {
+            continue;
+        };
+        any = true;
+        codes_row = codes_row.push(
+            column![
+                qr_code(data)
+                    .total_size(SETTINGS.qr_codes.size)
+                    .style(move |_: &Theme| qr_code::Style {
+                        cell: fade(Color::BLACK, alpha),
+                        background: fade(Color::WHITE, alpha),
+                    }),
+                text(caption.as_str())
+                    .size(22)
+                    .style(move |_: &Theme| text::Style { color: Some(fade(Color::BLACK, alpha)), ..Default::default() })
+            ]
+            .spacing(6)
+            .align_x(iced::alignment::Horizontal::Center)
+        );
+    }
+    if !any {
+        return None;
+    }
+
+    // A white card so the codes scan on top of images too
+    let card = container(codes_row)
+        .padding(12)
+        .style(move |_: &Theme| container::Style {
+            background: Some(fade(Color::WHITE, alpha).into()),
+            ..Default::default()
+        });
+
+    let (horizontal, vertical) = match SETTINGS.qr_codes.placement {
+        config::QrPlacement::TopLeft => (iced::alignment::Horizontal::Left, iced::alignment::Vertical::Top),
+        config::QrPlacement::TopRight => (iced::alignment::Horizontal::Right, iced::alignment::Vertical::Top),
+        config::QrPlacement::BottomLeft => (iced::alignment::Horizontal::Left, iced::alignment::Vertical::Bottom),
+        config::QrPlacement::BottomRight => (iced::alignment::Horizontal::Right, iced::alignment::Vertical::Bottom),
+    };
+
+    Some(
+        container(card)
+            .width(Length::Fixed(IMAGE_WIDTH + 2.0 * SLIDE_PADDING))
+            .height(Length::Fill)
+            .align_x(horizontal)
+            .align_y(vertical)
+            .padding(SLIDE_PADDING)
+            .into()
+    )
+}
+
+/// Small status dot in the corner; only spells out the state when something is wrong.
+fn connection_indicator<'a>(state: ConnectionState) -> Element<'a, Message> {
+    let (color, label) = match state {
+        ConnectionState::Online => (ONLINE_COLOR, None),
+        ConnectionState::Degraded => (DEGRADED_COLOR, Some(LOCALE.strings.reconnecting.as_str())),
+        ConnectionState::Offline => (OFFLINE_COLOR, Some(LOCALE.strings.offline.as_str())),
+    };
+
+    let mut indicator = row![
+        text("●")
+            .size(20)
+            .style(move |_: &Theme| text::Style { color: Some(color), ..Default::default() })
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center);
+
+    if let Some(label) = label {
+        indicator = indicator.push(
+            text(label)
+                .size(20)
+                .style(|_: &Theme| text::Style { color: Some(SECONDARY_TEXT_COLOR), ..Default::default() })
+        );
+    }
+
+    container(indicator)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(iced::alignment::Horizontal::Right)
+        .align_y(iced::alignment::Vertical::Bottom)
+        .padding(16)
+        .into()
+}
+
+fn realtime_events() -> impl iced::futures::Stream<Item = RealtimeEvent> {
+    let auth = SETTINGS.auth.clone().map(|config| {
+        std::sync::Arc::new(auth::Authenticator::new(config, SETTINGS.api_url.clone()))
+    });
+    realtime::subscribe(SETTINGS.api_url.clone(), SETTINGS.events_collection.clone(), auth)
+}
+
+fn local_content_changes() -> impl iced::futures::Stream<Item = ()> {
+    local::watch(SETTINGS.local_content_dirs())
+}
+
 impl Message {
-    fn handle_result(result: Result<Vec<Event>, anyhow::Error>) -> Self {
+    fn handle_result(result: Result<(Vec<Event>, Option<PartialFailure>), anyhow::Error>) -> Self {
         match result {
-            Ok(events) => Message::EventsLoaded(events),
-            Err(e) => Message::Error(e.to_string()),
+            Ok((events, failure)) => Message::EventsLoaded(events, failure),
+            Err(e) => Message::FetchFailed(e.to_string(), RetryAfter::from_error(&e)),
         }
     }
 }
 
-async fn fetch_events() -> Result<Vec<Event>, anyhow::Error> {
-    tracing::info!("Starting to fetch upcoming events from API");
-    let api_events = match API_CLIENT.fetch_events().await {
-        Ok(events) => {
-            tracing::info!("Successfully fetched {} upcoming events from API", events.len());
-            events
+/// The merged events, plus what went wrong if only some sources answered.
+async fn fetch_events() -> Result<(Vec<Event>, Option<PartialFailure>), anyhow::Error> {
+    tracing::info!("Starting to fetch upcoming events from all sources");
+    let merged = match EVENT_SOURCES.fetch_events().await {
+        Ok(merged) => {
+            tracing::info!("Fetched {} upcoming events from all sources", merged.events.len());
+            merged
         },
         Err(e) => {
-            tracing::error!("Failed to fetch events from API: {}", e);
+            tracing::error!("Failed to fetch events from sources: {}", e);
             return Err(e);
         }
     };
-    
+
+    // Events reused from failed sources would make the cache look fresher than it is
+    let failure = if merged.is_complete() {
+        if let Err(e) = cache::EventCache::save(&merged.events) {
+            tracing::warn!("Failed to write event cache: {}", e);
+        }
+        None
+    } else {
+        Some(PartialFailure {
+            error: format!("Some event sources failed: {}", merged.failures.join("; ")),
+            retry_after: merged.retry_after,
+        })
+    };
+
+    Ok((events_from_api(merged.events), failure))
+}
+
+fn load_cached_events() -> Option<Vec<Event>> {
+    let cache = match cache::EventCache::load() {
+        Ok(cache) => cache,
+        Err(e) => {
+            tracing::warn!("No usable event cache: {}", e);
+            return None;
+        }
+    };
+
+    // The cache may be old, so drop anything that has already finished
+    let now = chrono::Utc::now();
+    let api_events: Vec<ApiEvent> = cache.events
+        .into_iter()
+        .filter(|event| event.end_time > now)
+        .collect();
+
+    tracing::info!(
+        "Using {} cached events from {}",
+        api_events.len(),
+        cache.saved_at
+    );
+    Some(events_from_api(api_events))
+}
+
+fn events_from_api(api_events: Vec<ApiEvent>) -> Vec<Event> {
     // Convert API events to display events (no filtering needed since /upcoming endpoint handles it)
     let mut events: Vec<Event> = api_events
         .into_iter()
@@ -438,89 +1155,359 @@ async fn fetch_events() -> Result<Vec<Event>, anyhow::Error> {
     // Sort by start time (API should already provide them sorted, but ensure consistency)
     events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
     tracing::info!("Processed {} upcoming events", events.len());
-    Ok(events)
+    events
+}
+
+/// Every event followed by the slides from `[[slides]]`.
+fn all_slides(events: &[Event]) -> Vec<Slide> {
+    events
+        .iter()
+        .cloned()
+        .map(Slide::Event)
+        .chain(STATIC_SLIDES.iter().cloned())
+        .collect()
+}
+
+/// Fills agendas in `slides` from `events`, dropping the ones with nothing to list.
+fn with_events(slides: Vec<Slide>, events: &[Event]) -> Vec<Slide> {
+    slides.into_iter().filter_map(|slide| slide.with_events(events)).collect()
+}
+
+/// The non-event slides from `[[slides]]`, ready to show.
+fn static_slides() -> Vec<Slide> {
+    SETTINGS.slides
+        .iter()
+        .enumerate()
+        .map(|(index, config)| {
+            let kind = match &config.content {
+                config::SlideContent::Announcement { .. } => "announcement",
+                config::SlideContent::Image { .. } => "image",
+                config::SlideContent::Welcome { .. } => "welcome",
+                config::SlideContent::Widget { .. } => "widget",
+                config::SlideContent::Agenda { .. } => "agenda",
+            };
+            let timing = slide::Timing {
+                id: config.id.clone().unwrap_or_else(|| format!("{}-{}", kind, index + 1)),
+                weight: config.weight.unwrap_or(1).max(1),
+                duration: config.duration_seconds.map(Duration::from_secs),
+            };
+            static_slide(timing, &config.content)
+        })
+        .collect()
+}
+
+fn static_slide(timing: slide::Timing, content: &config::SlideContent) -> Slide {
+    match content {
+        config::SlideContent::Announcement { title, body } => Slide::Announcement(slide::Announcement {
+            timing,
+            title: title.clone(),
+            pages: announcement_pages(&richtext::parse(body)),
+        }),
+        config::SlideContent::Image { image, fit, caption } => Slide::Image(slide::ImageSlide {
+            timing,
+            url: if image.contains("://") { image.clone() } else { local::file_url(std::path::Path::new(image)) },
+            fit: *fit,
+            caption: caption.clone().filter(|caption| !caption.is_empty()),
+        }),
+        config::SlideContent::Welcome { title, subtitle } => Slide::Welcome(slide::Welcome {
+            timing,
+            title: title.clone(),
+            subtitle: subtitle.clone(),
+        }),
+        config::SlideContent::Widget { widget } => Slide::Widget(slide::Widget {
+            timing,
+            kind: *widget,
+        }),
+        config::SlideContent::Agenda { title, days, max_events, per_page } => Slide::Agenda(slide::Agenda {
+            timing,
+            title: if title.is_empty() { LOCALE.strings.agenda_title.clone() } else { title.clone() },
+            days: *days,
+            max_events: *max_events,
+            per_page: *per_page,
+            layout: agenda_layout(),
+            pages: Vec::new(),
+        }),
+    }
+}
+
+/// Every `[playlists]` entry, keyed by name.
+fn playlists() -> HashMap<String, playlist::Playlist> {
+    SETTINGS.playlists
+        .iter()
+        .map(|(name, entries)| {
+            let playlist = playlist::Playlist::new(name, entries, &STATIC_SLIDES, |id, content| {
+                // Durations come from the playlist entry
+                static_slide(slide::Timing { id, weight: 1, duration: None }, content)
+            });
+            (name.clone(), playlist)
+        })
+        .collect()
+}
+
+fn find_playlist(name: &str) -> Option<&'static playlist::Playlist> {
+    let playlist = PLAYLISTS.get(name);
+    if playlist.is_none() {
+        tracing::error!("Unknown playlist '{}', showing every event and slide", name);
+    }
+    playlist
+}
+
+/// The `[[schedule]]` rule in effect right now, in the church's zone.
+fn scheduled_rule() -> Option<usize> {
+    let now = chrono::Utc::now().with_timezone(&*TIMEZONE).naive_local();
+    schedule::active_rule(&SETTINGS.schedule, now)
 }
 
 async fn load_image(url: String) -> image::Handle {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .expect("Failed to create HTTP client");
+    if let Some(path) = url.strip_prefix("file://") {
+        return load_local_image(path).await;
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        tracing::error!("Not loading image {}: only web URLs and local content files are allowed", url);
+        return image::Handle::from_bytes(vec![]);
+    }
+
+    // A single conditional GET; the server answers 304 if we already have this image
+    let mut request = IMAGE_VALIDATORS.prepare(&url, IMAGE_CLIENT.get(&url));
+    if let Some(auth) = image_auth(&url) {
+        request = match auth.authorize(request).await {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::error!("Failed to authorize image request {}: {}", url, e);
+                return image::Handle::from_bytes(vec![]);
+            }
+        };
+    }
 
-    // First check the content length
-    let head_resp = match client.head(&url).send().await {
+    let response = match request
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+    {
         Ok(resp) => resp,
         Err(e) => {
-            tracing::error!("Failed to fetch image head {}: {}", url, e);
+            tracing::error!("Failed to fetch image {}: {}", url, e);
             return image::Handle::from_bytes(vec![]);
         }
     };
 
-    if let Some(content_length) = head_resp.content_length() {
+    if let Some(content_length) = response.content_length().filter(|_| response.status().is_success()) {
         tracing::info!("Image size for {}: {} KB", url, content_length / 1024);
-        if content_length > MAX_IMAGE_SIZE {
-            tracing::warn!("Image too large ({}KB), skipping download", content_length / 1024);
-            return image::Handle::from_bytes(vec![]);
-        }
     }
 
-    let response = match client.get(&url).send().await {
-        Ok(resp) => resp,
+    // Images over MAX_IMAGE_SIZE are rejected here, before they're stored
+    match IMAGE_VALIDATORS.body(&url, response).await {
+        Ok((bytes, _)) => {
+            tracing::info!("Successfully downloaded image {} with {} bytes", url, bytes.len());
+            image::Handle::from_bytes(bytes)
+        }
         Err(e) => {
-            tracing::error!("Failed to fetch image {}: {}", url, e);
-            return image::Handle::from_bytes(vec![]);
+            tracing::error!("Failed to get image bytes for {}: {}", url, e);
+            image::Handle::from_bytes(vec![])
         }
-    };
+    }
+}
+
+/// The credentials for `url` when it's on the API host of a source that
+/// needs them. Other hosts never see the credentials.
+fn image_auth(url: &str) -> Option<&'static auth::Authenticator> {
+    let origin = origin(url)?;
+    IMAGE_AUTH.iter().find(|(api, _)| *api == origin).map(|(_, auth)| auth.as_ref())
+}
 
-    match response.bytes().await {
+/// Scheme, host and port of `url`, e.g. `https://example.org:8090`.
+fn origin(url: &str) -> Option<String> {
+    reqwest::Url::parse(url).ok().map(|url| url.origin().ascii_serialization())
+}
+
+async fn load_local_image(path: &str) -> image::Handle {
+    match tokio::fs::read(path).await {
         Ok(bytes) => {
             if bytes.len() as u64 > MAX_IMAGE_SIZE {
-                tracing::warn!("Image too large after download ({}KB), skipping", bytes.len() / 1024);
+                tracing::warn!("Local image too large ({}KB), skipping", bytes.len() / 1024);
                 return image::Handle::from_bytes(vec![]);
             }
-            tracing::info!("Successfully downloaded image {} with {} bytes", url, bytes.len());
-            image::Handle::from_bytes(bytes.to_vec())
+            tracing::info!("Successfully read local image {} with {} bytes", path, bytes.len());
+            image::Handle::from_bytes(bytes)
         }
         Err(e) => {
-            tracing::error!("Failed to get image bytes for {}: {}", url, e);
+            tracing::error!("Failed to read local image {}: {}", path, e);
             image::Handle::from_bytes(vec![])
         }
     }
 }
 
+impl Event {
+    fn has_ended(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.end_timestamp > self.timestamp {
+            return self.end_timestamp <= now;
+        }
+
+        // Events without a real end stay up for the rest of their day in the church's zone
+        self.timestamp.with_timezone(&*TIMEZONE).date_naive() < timezone::today(*TIMEZONE)
+    }
+
+    fn slide_info(&self) -> rotation::SlideInfo {
+        rotation::SlideInfo {
+            id: self.id.clone(),
+            weight: SETTINGS.slide_weight(&self.id, &self.title, self.is_featured),
+            is_featured: self.is_featured,
+            category: self.category.clone(),
+            start: Some(self.timestamp),
+        }
+    }
+
+    /// How long one page of the description stays up; the title and location
+    /// count towards every page since they stay on screen.
+    fn page_duration(&self, page: usize) -> Duration {
+        if let Some(duration) = self.duration {
+            return duration;
+        }
+
+        let page_text = self.description_pages
+            .get(page)
+            .map(|lines| richtext::plain_text(lines))
+            .unwrap_or_default();
+        let words = [self.title.as_str(), self.location.as_str(), page_text.as_str()]
+            .iter()
+            .map(|text| text.split_whitespace().count())
+            .sum();
+        SETTINGS.slide_duration(&self.id, &self.title, &self.category, self.is_featured, words)
+    }
+}
+
 impl From<ApiEvent> for Event {
-    fn from(event: ApiEvent) -> Self {
-        let clean_description = html2text::from_read(event.description.as_bytes(), 80)
-            .replace('\n', " ")
-            .split_whitespace()
-            .collect::<Vec<&str>>()
-            .join(" ");
+    fn from(mut event: ApiEvent) -> Self {
+        // Every source ends up here, including realtime updates and the cache
+        event.drop_non_web_images(&SETTINGS.local_content_dirs());
+        let description = richtext::parse(&event.description);
 
-        let date = event.start_time.format("%A, %B %d, %Y").to_string();
-        let start_time = event.start_time.format("%I:%M %p").to_string().trim_start_matches('0').to_string();
-        let end_time = event.end_time.format("%I:%M %p").to_string().trim_start_matches('0').to_string();
+        // Shown in the church's zone, not UTC, so DST changes are handled by chrono-tz
+        let local_start = event.start_time.with_timezone(&*TIMEZONE);
+        let local_end = event.end_time.with_timezone(&*TIMEZONE);
+        let date = LOCALE.format_date(&local_start);
+        let start_time = LOCALE.format_time(&local_start);
+        let end_time = LOCALE.format_time(&local_end);
 
         let image_url = event.image.clone();
         if let Some(ref url) = image_url {
             tracing::info!("Using image URL: {}", url);
         }
 
+        let (title_area, category_area, location_area) = text_areas();
+        let title_text = fit::fit(&event.title, title_area);
+        let category_text = fit::fit(&event.category.to_uppercase(), category_area);
+        let location_text = fit::fit(&event.location, location_area);
+        let description_pages = description_pages(&description, &category_text, &location_text);
+        let registration_url = event.registration_url
+            .filter(|url| !url.is_empty())
+            .or_else(|| SETTINGS.registration_url(&event.id, &event.title).map(str::to_string));
+
         Self {
+            id: event.id,
+            title_text,
+            category_text,
+            location_text,
             title: event.title,
-            description: clean_description,
+            description_pages,
             start_time,
             end_time,
             date,
             location: event.location,
-            //location_url: event.location_url,
+            location_url: event.location_url.filter(|url| !url.is_empty()),
+            registration_url,
             image_url,
             category: event.category,
-            //is_featured: event.is_featured,
+            is_featured: event.is_featured,
             timestamp: event.start_time,
+            end_timestamp: event.end_time,
+            duration: None,
         }
     }
 }
 
+/// Space for the title, category badge and location, from the same sizes and
+/// paddings `view` uses.
+fn text_areas() -> (fit::Area, fit::Area, fit::Area) {
+    let right_column = SETTINGS.window_width as f32 - 2.0 * SLIDE_PADDING - IMAGE_WIDTH - COLUMN_SPACING;
+
+    let title = fit::Area {
+        width: IMAGE_WIDTH - 2.0 * DETAIL_PADDING,
+        max_lines: SETTINGS.title_max_lines.max(1),
+        min_size: 48.0,
+        max_size: 96.0,
+        font: iced::Font::DEFAULT,
+    };
+    let category = fit::Area {
+        width: right_column - 2.0 * BADGE_PADDING,
+        max_lines: 1,
+        min_size: 24.0,
+        max_size: 36.0,
+        font: iced::Font::DEFAULT,
+    };
+    // Minus the location icon and the gap after it
+    let location = fit::Area {
+        width: right_column - 2.0 * DETAIL_PADDING - 48.0 - 15.0,
+        max_lines: SETTINGS.location_max_lines.max(1),
+        min_size: 32.0,
+        max_size: 48.0,
+        font: iced::Font::DEFAULT,
+    };
+    (title, category, location)
+}
+
+/// Splits a description into pages that fit the description box, whose size
+/// is worked out from the same sizes and paddings `view` uses.
+fn description_pages(
+    description: &[richtext::Line],
+    category: &fit::Fitted,
+    location: &fit::Fitted,
+) -> Vec<Vec<richtext::Line>> {
+    let line = |size: f32| size * LINE_HEIGHT;
+
+    // Window minus outer padding, image column, row spacing and box padding
+    let width = SETTINGS.window_width as f32 - 2.0 * SLIDE_PADDING - IMAGE_WIDTH - COLUMN_SPACING - 2.0 * DESCRIPTION_PADDING;
+
+    let category = line(category.size) + 2.0 * BADGE_PADDING;
+    let date_and_time = line(64.0) + 15.0 + line(56.0) + 2.0 * DETAIL_PADDING;
+    // Without a location an empty text at the default size takes its place.
+    // With one, assume it uses all the lines it may.
+    let location = if location.text.is_empty() {
+        line(16.0)
+    } else {
+        line(location.size) * SETTINGS.location_max_lines.max(1) as f32 + 2.0 * DETAIL_PADDING
+    };
+    let above = category + date_and_time + location + 3.0 * DETAILS_SPACING;
+    let height = SETTINGS.window_height as f32
+        - 2.0 * SLIDE_PADDING
+        - above
+        - 2.0 * DESCRIPTION_PADDING
+        - (line(PAGE_INDICATOR_SIZE) + 10.0);
+
+    paginate_text(description, width, height)
+}
+
+/// Splits an announcement's text into pages that fit below its title, from
+/// the sizes and paddings `announcement_view` uses.
+fn announcement_pages(body: &[richtext::Line]) -> Vec<Vec<richtext::Line>> {
+    let line = |size: f32| size * LINE_HEIGHT;
+
+    let width = SETTINGS.window_width as f32 - 2.0 * 60.0 - 2.0 * 25.0;
+    // Assumes a one-line title
+    let height = SETTINGS.window_height as f32
+        - 2.0 * 60.0
+        - (line(ANNOUNCEMENT_TITLE_SIZE) + 40.0)
+        - 2.0 * 25.0
+        - (line(PAGE_INDICATOR_SIZE) + 40.0);
+
+    paginate_text(body, width, height)
+}
+
+/// Pages of formatted text for a box of the given inner size.
+fn paginate_text(text: &[richtext::Line], width: f32, height: f32) -> Vec<Vec<richtext::Line>> {
+    pagination::paginate(text, height, DESCRIPTION_SPACING, |line| line_height(line, width))
+}
+
 fn main() -> iced::Result {
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
@@ -624,21 +1611,394 @@ fn main() -> iced::Result {
         || {
             let mut state = DigitalSign::default();
             state.is_fetching = true;
+
+            // Show the cached events right away while the first fetch is in flight
+            let cached = match load_cached_events() {
+                Some(events) => Task::done(Message::CachedEventsLoaded(events)),
+                None => Task::none(),
+            };
             (
                 state,
-                Task::perform(
-                    fetch_events(),
-                    Message::handle_result
-                )
+                Task::batch([
+                    cached,
+                    Task::perform(
+                        fetch_events(),
+                        Message::handle_result
+                    ),
+                ])
             )
         }
     )
 }
 
 impl DigitalSign {
+    /// Reconciles a freshly fetched list with the one on screen by event id,
+    /// keeping images that are still needed and staying on the current event.
+    fn apply_events(&mut self, events: Vec<Event>) -> Task<Message> {
+        let current = self.current_slide();
+
+        let old_events: HashMap<&str, &Event> = self.events
+            .iter()
+            .map(|e| (e.id.as_str(), e))
+            .collect();
+        let mut added = 0;
+        let mut changed = 0;
+        for event in &events {
+            match old_events.get(event.id.as_str()) {
+                None => added += 1,
+                Some(old) if *old != event => changed += 1,
+                Some(_) => {}
+            }
+        }
+        let new_ids: HashSet<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        let removed = self.events.iter().filter(|e| !new_ids.contains(e.id.as_str())).count();
+        tracing::info!(
+            "Event list diff: {} added, {} changed, {} removed, {} unchanged",
+            added,
+            changed,
+            removed,
+            events.len() - added - changed
+        );
+
+        self.events = events;
+        self.rebuild_slides();
+
+        // Only drop images no slide refers to anymore
+        let before = self.loaded_images.len();
+        self.loaded_images.retain(|url, _| {
+            self.slides.iter().any(|slide| slide.image_urls().contains(&url.as_str()))
+        });
+        tracing::info!("Kept {} of {} loaded images", self.loaded_images.len(), before);
+        IMAGE_VALIDATORS.retain(|url| {
+            self.slides.iter().any(|slide| slide.image_urls().contains(&url))
+        });
+
+        self.update_qr_codes();
+        self.restore_current_slide(current);
+
+        // Load missing images, starting with the one on screen
+        let mut queued = HashSet::new();
+        let order = std::iter::once(self.current_slide_index)
+            .chain((0..self.slides.len()).filter(|index| *index != self.current_slide_index));
+
+        let mut image_tasks = Vec::new();
+        for index in order {
+            let urls = self.slides.get(index).map(Slide::image_urls).unwrap_or_default();
+            for url in urls.into_iter().map(str::to_string) {
+                if self.loaded_images.contains_key(&url) || !queued.insert(url.clone()) {
+                    continue;
+                }
+
+                tracing::info!("Queueing image load for: {}", url);
+                image_tasks.push(Task::perform(
+                    load_image(url.clone()),
+                    move |handle| Message::ImageLoaded(url.clone(), handle)
+                ));
+            }
+        }
+
+        if !image_tasks.is_empty() {
+            tracing::info!("Starting load of {} images", image_tasks.len());
+            Task::batch(image_tasks)
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Generates QR codes for links that are new and drops the ones no event uses anymore.
+    fn update_qr_codes(&mut self) {
+        if !SETTINGS.qr_codes.enabled {
+            return;
+        }
+
+        let urls: HashSet<&String> = self.events
+            .iter()
+            .flat_map(|e| [e.location_url.as_ref(), e.registration_url.as_ref()])
+            .flatten()
+            .collect();
+        self.qr_codes.retain(|url, _| urls.contains(url));
+
+        for url in urls {
+            if self.qr_codes.contains_key(url) {
+                continue;
+            }
+            match qr_code::Data::new(url.as_bytes()) {
+                Ok(data) => {
+                    tracing::info!("Generated QR code for {}", url);
+                    self.qr_codes.insert(url.clone(), data);
+                }
+                Err(e) => tracing::warn!("Could not make a QR code for {}: {}", url, e),
+            }
+        }
+    }
+
+    fn apply_realtime(&mut self, event: RealtimeEvent) -> Task<Message> {
+        match event {
+            RealtimeEvent::Connected => {
+                tracing::info!("Realtime stream connected, pausing polling");
+                self.realtime_connected = true;
+
+                // Catch up on anything that changed while the stream was down
+                if self.is_fetching {
+                    Task::none()
+                } else {
+                    self.is_fetching = true;
+                    Task::perform(fetch_events(), Message::handle_result)
+                }
+            }
+            RealtimeEvent::Created(event) | RealtimeEvent::Updated(event) => {
+                self.upsert_event(event)
+            }
+            RealtimeEvent::Deleted(id) => {
+                self.remove_event(&id);
+                Task::none()
+            }
+            RealtimeEvent::Disconnected(reason) => {
+                if self.realtime_connected {
+                    tracing::warn!("Realtime stream dropped ({}), falling back to polling", reason);
+                }
+                self.realtime_connected = false;
+                Task::none()
+            }
+        }
+    }
+
+    fn upsert_event(&mut self, api_event: ApiEvent) -> Task<Message> {
+        let current = self.current_slide();
+
+        // A recurring event replaces all of its instances
+        let master_id = api_event.id.clone();
+        self.events.retain(|e| !recurrence::is_same_series(&e.id, &master_id));
+
+        let instances = recurrence::expand_event(api_event, SETTINGS.recurring_horizon_days, *TIMEZONE);
+        let now = chrono::Utc::now();
+        let instances: Vec<Event> = instances
+            .into_iter()
+            .filter(|instance| instance.end_time > now)
+            .map(Event::from)
+            .collect();
+        if instances.is_empty() {
+            tracing::info!("Realtime event {} has already ended, not showing it", master_id);
+            self.rebuild_slides();
+            self.restore_current_slide(current);
+            return Task::none();
+        }
+
+        let image_url = instances[0].image_url.clone();
+        self.events.extend(instances);
+        self.events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        self.rebuild_slides();
+        self.update_qr_codes();
+        self.restore_current_slide(current);
+
+        match image_url {
+            Some(url) if !self.loaded_images.contains_key(&url) => {
+                tracing::info!("Loading image for realtime event: {}", url);
+                Task::perform(
+                    load_image(url.clone()),
+                    move |handle| Message::ImageLoaded(url.clone(), handle)
+                )
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn remove_event(&mut self, id: &str) {
+        let current = self.current_slide();
+        self.events.retain(|e| !recurrence::is_same_series(&e.id, id));
+        self.rebuild_slides();
+        self.restore_current_slide(current);
+    }
+
+    /// Drops events that are over, so the sign doesn't wait for the next fetch to hide them.
+    fn expire_finished_events(&mut self) {
+        let now = chrono::Utc::now();
+        if !self.events.iter().any(|e| e.has_ended(now)) {
+            return;
+        }
+
+        let current = self.current_slide();
+        self.events.retain(|e| {
+            let ended = e.has_ended(now);
+            if ended {
+                tracing::info!("Event '{}' has ended, removing it", e.title);
+            }
+            !ended
+        });
+        self.rebuild_slides();
+        self.restore_current_slide(current);
+    }
+
+    /// Switches to another playlist or set of categories when the schedule
+    /// moves into a different rule, without waiting for the current slide.
+    fn follow_schedule(&mut self) {
+        // Agendas list the days from today, so move them on at midnight
+        let today = timezone::today(*TIMEZONE);
+        if today != self.slides_date {
+            self.slides_date = today;
+            let current = self.current_slide();
+            self.rebuild_slides();
+            self.restore_current_slide(current);
+        }
+
+        let rule = scheduled_rule();
+        if rule == self.schedule_rule {
+            return;
+        }
+
+        self.schedule_rule = rule;
+        let current = self.current_slide();
+        self.select_content();
+        self.restore_current_slide(current.clone());
+        if self.current_slide().map(|slide| slide.id().to_string()) != current.map(|slide| slide.id().to_string()) {
+            // The new content gets its full time on screen
+            self.last_update = Instant::now();
+        }
+    }
+
+    /// Sets the playlist and categories for the schedule rule in effect, or
+    /// the configured `playlist` outside of all rules.
+    fn select_content(&mut self) {
+        let rule = self.schedule_rule.and_then(|index| SETTINGS.schedule.get(index));
+        let playlist = match rule {
+            Some(rule) => {
+                tracing::info!("Schedule rule '{}' is now in effect", rule.name);
+                rule.playlist.as_deref()
+            }
+            None => {
+                if !SETTINGS.schedule.is_empty() {
+                    tracing::info!("No schedule rule in effect");
+                }
+                SETTINGS.playlist.as_deref()
+            }
+        };
+
+        self.playlist = playlist.and_then(find_playlist);
+        self.categories = rule.map(|rule| rule.categories.clone()).unwrap_or_default();
+        // A playlist sets the order itself
+        self.rotation = match self.playlist {
+            Some(_) => Box::new(rotation::InOrder::default()),
+            None => rotation::from_config(SETTINGS.rotation, SETTINGS.rotation_half_life_days),
+        };
+        self.rebuild_slides();
+    }
+
+    /// The active playlist's slides, or else every event followed by the
+    /// slides from the config. Only events in `categories` take part.
+    fn rebuild_slides(&mut self) {
+        let events: Vec<Event> = self.events
+            .iter()
+            .filter(|e| {
+                self.categories.is_empty()
+                    || self.categories.iter().any(|category| category.eq_ignore_ascii_case(&e.category))
+            })
+            .cloned()
+            .collect();
+
+        let slides = match self.playlist {
+            Some(playlist) => playlist.slides(&events),
+            None => all_slides(&events),
+        };
+        self.slides = with_events(slides, &events);
+
+        if self.slides.is_empty() && (self.playlist.is_some() || !self.categories.is_empty()) {
+            tracing::warn!("Nothing to show for the current playlist or categories, showing everything");
+            self.slides = with_events(all_slides(&self.events), &self.events);
+        }
+    }
+
+    /// The slide on screen, used to find it again after the list changes and
+    /// to animate it away if it's gone.
+    fn current_slide(&self) -> Option<Slide> {
+        self.slides.get(self.current_slide_index).cloned()
+    }
+
+    /// Keeps the same slide on screen after the list changed. If an event on
+    /// screen is gone, moves on to the next one in time rather than an
+    /// unrelated slide.
+    fn restore_current_slide(&mut self, current: Option<Slide>) {
+        let Some(previous) = current else {
+            if self.current_slide_index >= self.slides.len() {
+                self.current_slide_index = 0;
+            }
+            if !self.slides.is_empty() {
+                // The first slides replace the loading message
+                self.start_transition(None, 0);
+            }
+            return;
+        };
+
+        // A playlist can list a slide more than once, so prefer the copy already on screen
+        let unmoved = self.slides
+            .get(self.current_slide_index)
+            .map_or(false, |slide| slide.id() == previous.id());
+        let index = unmoved
+            .then_some(self.current_slide_index)
+            .or_else(|| self.slides.iter().position(|slide| slide.id() == previous.id()))
+            .or_else(|| {
+                let start = previous.as_event()?.timestamp;
+                self.slides
+                    .iter()
+                    .position(|slide| slide.as_event().map_or(false, |e| e.timestamp >= start))
+            })
+            .unwrap_or(0);
+
+        if index != self.current_slide_index {
+            tracing::info!("Current slide moved from index {} to {}", self.current_slide_index, index);
+        }
+        if self.slides.get(index).map_or(true, |slide| slide.id() != previous.id()) {
+            // A different slide took its place, so start its text from the top
+            let page = self.current_page;
+            self.current_page = 0;
+            self.start_transition(Some(previous), page);
+        }
+        self.current_slide_index = index;
+    }
+
+    /// Animates from `from` to whatever is on screen next, unless transitions are off.
+    fn start_transition(&mut self, from: Option<Slide>, page: usize) {
+        self.transition = transition::Transition::start(Outgoing { slide: from, page }, &SETTINGS.transitions);
+    }
+
+    fn current_slide_duration(&self) -> Duration {
+        self.slides
+            .get(self.current_slide_index)
+            .map_or_else(|| SETTINGS.slide_interval(), |slide| slide.page_duration(self.current_page))
+    }
+
+    /// Shows the next page of the current slide's text, if there is one, so
+    /// the slide stays up until all of it has been read.
+    fn advance_page(&mut self) -> bool {
+        let Some(slide) = self.slides.get(self.current_slide_index) else {
+            return false;
+        };
+        if self.current_page + 1 >= slide.page_count() {
+            return false;
+        }
+
+        self.current_page += 1;
+        self.last_update = Instant::now();
+        tracing::info!(
+            "Showing page {} of {} for slide '{}'",
+            self.current_page + 1,
+            slide.page_count(),
+            slide.id()
+        );
+        true
+    }
+
     fn should_refresh(&self) -> bool {
+        // After a failure the backoff decides when to try again, not the regular interval
+        if self.health.is_retrying() {
+            return self.health.retry_due();
+        }
+
         let elapsed = self.last_refresh.elapsed();
-        let interval = SETTINGS.refresh_interval();
+        let interval = if self.realtime_connected {
+            REALTIME_RESYNC_INTERVAL
+        } else {
+            SETTINGS.refresh_interval()
+        };
         let should_refresh = elapsed >= interval;
         tracing::info!(
             "Checking refresh: elapsed={:?}, interval={:?}, should_refresh={}",
@@ -652,14 +2012,136 @@ impl DigitalSign {
 
 impl Default for DigitalSign {
     fn default() -> Self {
-        Self {
+        let mut sign = Self {
             events: vec![],
-            current_event_index: 0,
+            slides: vec![],
+            playlist: None,
+            categories: vec![],
+            schedule_rule: scheduled_rule(),
+            slides_date: timezone::today(*TIMEZONE),
+            current_slide_index: 0,
             last_update: Instant::now(),
             last_refresh: Instant::now(),
             loaded_images: std::collections::HashMap::new(),
             loading_frame: 0,
             is_fetching: false,
+            realtime_connected: false,
+            health: ConnectionHealth::new(),
+            rotation: rotation::from_config(SETTINGS.rotation, SETTINGS.rotation_half_life_days),
+            current_page: 0,
+            qr_codes: HashMap::new(),
+            transition: None,
+        };
+        sign.select_content();
+        sign
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(id: &str, hour: u32, image: Option<&str>) -> Event {
+        let start = chrono::Utc.with_ymd_and_hms(2030, 1, 1, hour, 0, 0).unwrap();
+        Event::from(ApiEvent {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: image.map(String::from),
+            thumbnail: None,
+            category: String::new(),
+            is_featured: false,
+            recurring_type: None,
+            created_at: start,
+            updated_at: start,
+        })
+    }
+
+    fn sign(events: Vec<Event>, current: usize) -> DigitalSign {
+        let mut sign = DigitalSign {
+            events,
+            current_slide_index: current,
+            ..DigitalSign::default()
+        };
+        sign.rebuild_slides();
+        sign
+    }
+
+    #[test]
+    fn current_event_stays_on_screen_after_refresh() {
+        let mut sign = sign(vec![event("a", 9, None), event("b", 10, None), event("c", 11, None)], 1);
+
+        let _ = sign.apply_events(vec![event("new", 8, None), event("a", 9, None), event("b", 10, None)]);
+
+        assert_eq!(sign.slides[sign.current_slide_index].id(), "b");
+    }
+
+    #[test]
+    fn slides_are_rebuilt_when_the_date_changes() {
+        let mut sign = sign(vec![event("a", 9, None), event("b", 10, None)], 0);
+        sign.events.push(event("c", 11, None));
+        sign.follow_schedule();
+        assert!(!sign.slides.iter().any(|slide| slide.id() == "c"));
+
+        sign.slides_date = sign.slides_date.pred_opt().unwrap();
+        sign.follow_schedule();
+        assert_eq!(sign.slides_date, timezone::today(*TIMEZONE));
+        assert!(sign.slides.iter().any(|slide| slide.id() == "c"));
+    }
+
+    #[test]
+    fn origins_compare_scheme_host_and_port() {
+        assert_eq!(origin("https://api.example.org/api/files/events/1/a.png").as_deref(), Some("https://api.example.org"));
+        assert_eq!(origin("http://localhost:8090/api").as_deref(), Some("http://localhost:8090"));
+        assert_ne!(origin("https://api.example.org.evil.com/a.png"), origin("https://api.example.org"));
+        assert_eq!(origin("slides/poster.png"), None);
+    }
+
+    #[test]
+    fn events_only_keep_web_images() {
+        assert_eq!(event("a", 9, Some("file:///etc/passwd")).image_url, None);
+        assert_eq!(event("b", 9, Some("/etc/passwd")).image_url, None);
+        assert_eq!(event("c", 9, Some("https://img/c.png")).image_url.as_deref(), Some("https://img/c.png"));
+    }
+
+    #[test]
+    fn removed_event_moves_on_to_the_next_one() {
+        let mut sign = sign(vec![event("a", 9, None), event("b", 10, None), event("c", 11, None)], 1);
+
+        sign.remove_event("b");
+
+        assert_eq!(sign.slides[sign.current_slide_index].id(), "c");
+    }
+
+    #[test]
+    fn images_still_in_use_are_kept() {
+        let mut sign = sign(vec![event("a", 9, Some("http://img/a.png")), event("b", 10, Some("http://img/b.png"))], 0);
+        for url in ["http://img/a.png", "http://img/b.png"] {
+            sign.loaded_images.insert(url.to_string(), image::Handle::from_path(url));
         }
+
+        let _ = sign.apply_events(vec![event("a", 9, Some("http://img/a.png"))]);
+
+        assert!(sign.loaded_images.contains_key("http://img/a.png"));
+        assert!(!sign.loaded_images.contains_key("http://img/b.png"));
+    }
+
+    #[test]
+    fn qr_codes_follow_the_event_links() {
+        let mut with_map = event("a", 9, None);
+        with_map.location_url = Some(String::from("https://maps.example/hall"));
+        let mut sign = sign(vec![], 0);
+
+        let _ = sign.apply_events(vec![with_map, event("b", 10, None)]);
+        assert_eq!(sign.qr_codes.keys().collect::<Vec<_>>(), ["https://maps.example/hall"]);
+
+        let _ = sign.apply_events(vec![event("b", 10, None)]);
+        assert!(sign.qr_codes.is_empty());
     }
 }
@@ -0,0 +1,213 @@
+use crate::richtext::{Line, LineKind, Span};
+
+/// A word and the style of the span it came from.
+struct Word<'a> {
+    span: &'a Span,
+    text: &'a str,
+    // Whether there was whitespace before it, which matters where spans meet mid-word
+    spaced: bool,
+}
+
+/// Splits a description into pages no taller than `height`. `measure` gives
+/// the height of a line once wrapped to the page width, and `spacing` is the
+/// gap between lines. A paragraph that doesn't fit is continued on the next
+/// page, split between words.
+pub fn paginate(lines: &[Line], height: f32, spacing: f32, measure: impl Fn(&Line) -> f32) -> Vec<Vec<Line>> {
+    let mut pages: Vec<Vec<Line>> = Vec::new();
+    let mut page: Vec<Line> = Vec::new();
+    let mut used = 0.0;
+
+    let mut flush = |page: &mut Vec<Line>, used: &mut f32| {
+        while page.last().map_or(false, |line| line.kind == LineKind::Blank) {
+            page.pop();
+        }
+        if !page.is_empty() {
+            pages.push(std::mem::take(page));
+        }
+        *used = 0.0;
+    };
+
+    for line in lines {
+        if line.kind == LineKind::Blank {
+            // No gap at the top of a page
+            if page.is_empty() {
+                continue;
+            }
+            let needed = spacing + measure(line);
+            if used + needed > height {
+                flush(&mut page, &mut used);
+                continue;
+            }
+            page.push(line.clone());
+            used += needed;
+            continue;
+        }
+
+        let mut rest = line.clone();
+        loop {
+            let gap = if page.is_empty() { 0.0 } else { spacing };
+            let needed = gap + measure(&rest);
+            if used + needed <= height {
+                page.push(rest);
+                used += needed;
+                break;
+            }
+
+            // Fill what's left of this page and carry the rest over. A page that
+            // can't even take one word of it is closed first.
+            let words = words(&rest);
+            let fitting = words_fitting(&words, |count| used + gap + measure(&rebuild(rest.kind, &words[..count])) <= height);
+            let fits = match fitting {
+                // A word taller or wider than the page; show it anyway
+                0 if page.is_empty() => 1,
+                0 => {
+                    flush(&mut page, &mut used);
+                    continue;
+                }
+                fits => fits,
+            };
+            if fits >= words.len() {
+                page.push(rest);
+                flush(&mut page, &mut used);
+                break;
+            }
+
+            let (head, tail) = (rebuild(rest.kind, &words[..fits]), rebuild(rest.kind, &words[fits..]));
+            page.push(head);
+            flush(&mut page, &mut used);
+            rest = tail;
+        }
+    }
+    flush(&mut page, &mut used);
+
+    if pages.is_empty() {
+        pages.push(Vec::new());
+    }
+    pages
+}
+
+fn words(line: &Line) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut pending_space = false;
+    for span in &line.spans {
+        let mut rest = span.text.as_str();
+        loop {
+            let trimmed = rest.trim_start();
+            pending_space |= trimmed.len() != rest.len();
+            if trimmed.is_empty() {
+                break;
+            }
+            let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+            words.push(Word { span, text: &trimmed[..end], spaced: pending_space && !words.is_empty() });
+            pending_space = false;
+            rest = &trimmed[end..];
+        }
+    }
+    words
+}
+
+/// The most words, short of all of them, that still `fit`. Like `fit::fit`,
+/// this measures candidates rather than estimating.
+fn words_fitting(words: &[Word], fit: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, words.len().saturating_sub(1));
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if fit(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+fn rebuild(kind: LineKind, words: &[Word]) -> Line {
+    let mut spans: Vec<Span> = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        let space = if word.spaced && index > 0 { " " } else { "" };
+        match spans.last_mut() {
+            Some(last) if std::ptr::eq(words[index - 1].span, word.span) => {
+                last.text.push_str(space);
+                last.text.push_str(word.text);
+            }
+            _ => {
+                // Don't let an underlined link swallow the space next to it
+                let mut text = word.text.to_string();
+                match spans.last_mut() {
+                    Some(last) if !last.link => last.text.push_str(space),
+                    _ => text.insert_str(0, space),
+                }
+                spans.push(Span { text, ..word.span.clone() });
+            }
+        }
+    }
+    Line { kind, spans }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::richtext;
+
+    /// One unit of height per line of `width` characters, wrapping at spaces.
+    fn lines_of(width: usize) -> impl Fn(&Line) -> f32 {
+        move |line| {
+            let mut lines = 1;
+            let mut column = 0;
+            for word in line.text().split_whitespace() {
+                let length = word.chars().count();
+                if column > 0 && column + 1 + length > width {
+                    lines += 1;
+                    column = length;
+                } else {
+                    column += if column > 0 { 1 } else { 0 } + length;
+                }
+            }
+            lines as f32
+        }
+    }
+
+    fn texts(pages: &[Vec<Line>]) -> Vec<Vec<String>> {
+        pages.iter().map(|page| page.iter().map(Line::text).collect()).collect()
+    }
+
+    #[test]
+    fn paragraph_continues_on_the_next_page() {
+        let lines = richtext::parse("one two three four five six seven");
+        let pages = paginate(&lines, 2.0, 0.0, lines_of(9));
+        assert_eq!(texts(&pages), [vec!["one two three"], vec!["four five six seven"]]);
+    }
+
+    #[test]
+    fn pages_do_not_start_or_end_with_gaps() {
+        let lines = richtext::parse("First\n\nSecond\n\nThird");
+        let pages = paginate(&lines, 2.0, 0.0, lines_of(20));
+        assert_eq!(texts(&pages), [vec!["First"], vec!["Second"], vec!["Third"]]);
+    }
+
+    #[test]
+    fn styles_survive_splitting() {
+        let lines = richtext::parse("plain **bold words here**");
+        let pages = paginate(&lines, 1.0, 0.0, lines_of(11));
+        let spans: Vec<_> = pages[1][0].spans.iter().map(|span| (span.text.as_str(), span.strong)).collect();
+        assert_eq!(spans, [("words here", true)]);
+    }
+
+    #[test]
+    fn word_taller_than_the_page_is_shown_anyway() {
+        let lines = richtext::parse("enormous");
+        assert_eq!(texts(&paginate(&lines, 0.5, 0.0, lines_of(20))), [vec!["enormous"]]);
+    }
+
+    #[test]
+    fn empty_description_is_one_empty_page() {
+        assert_eq!(paginate(&[], 3.0, 0.0, lines_of(10)), vec![Vec::<Line>::new()]);
+    }
+
+    #[test]
+    fn spacing_counts_between_lines() {
+        let lines = richtext::parse("one\ntwo\nthree");
+        let pages = paginate(&lines, 3.0, 0.5, lines_of(20));
+        assert_eq!(texts(&pages), [vec!["one", "two"], vec!["three"]]);
+    }
+}
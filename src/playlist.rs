@@ -0,0 +1,181 @@
+use crate::config::{PlaylistContent, PlaylistEntry, SlideContent};
+use crate::slide::Slide;
+use crate::Event;
+use std::time::Duration;
+
+/// Where the slides of one entry come from.
+#[derive(Debug, Clone)]
+enum Source {
+    Events { category: Option<String> },
+    Slide(Slide),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    source: Source,
+    duration: Option<Duration>,
+    repeat: u32,
+}
+
+/// A playlist from the config, with its slide references looked up.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    pub name: String,
+    entries: Vec<Entry>,
+}
+
+impl Playlist {
+    /// `slides` are the `[[slides]]` entries may refer to by id; `inline`
+    /// builds a slide written out in the playlist from an id and its content.
+    pub fn new(
+        name: &str,
+        entries: &[PlaylistEntry],
+        slides: &[Slide],
+        inline: impl Fn(String, &SlideContent) -> Slide,
+    ) -> Self {
+        let entries = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let source = match &entry.content {
+                    PlaylistContent::Events { category } => Source::Events {
+                        category: category.clone().filter(|category| !category.is_empty()),
+                    },
+                    PlaylistContent::Slide { slide } => match slides.iter().find(|s| s.id() == slide) {
+                        Some(found) => Source::Slide(found.clone()),
+                        None => {
+                            tracing::error!("Playlist '{}' refers to unknown slide '{}', skipping it", name, slide);
+                            return None;
+                        }
+                    },
+                    PlaylistContent::Inline(content) => {
+                        Source::Slide(inline(format!("{}-{}", name, index + 1), content))
+                    }
+                };
+
+                Some(Entry {
+                    source,
+                    duration: entry.duration_seconds.map(Duration::from_secs),
+                    repeat: entry.repeat.max(1),
+                })
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            entries,
+        }
+    }
+
+    /// The slides to play for the current events, in order. A slide appears
+    /// once for every time its entry repeats.
+    pub fn slides(&self, events: &[Event]) -> Vec<Slide> {
+        let mut slides = Vec::new();
+
+        for entry in &self.entries {
+            let group: Vec<Slide> = match &entry.source {
+                Source::Events { category } => events
+                    .iter()
+                    .filter(|event| {
+                        category
+                            .as_deref()
+                            .map_or(true, |category| event.category.eq_ignore_ascii_case(category))
+                    })
+                    .cloned()
+                    .map(Slide::Event)
+                    .collect(),
+                Source::Slide(slide) => vec![slide.clone()],
+            };
+
+            for _ in 0..entry.repeat {
+                slides.extend(group.iter().cloned().map(|slide| slide.with_duration(entry.duration)));
+            }
+        }
+
+        slides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocketbase::ApiEvent;
+    use crate::slide::{Timing, Welcome};
+
+    fn event(id: &str, category: &str) -> Event {
+        let start = chrono::Utc::now() + chrono::Duration::days(1);
+        Event::from(ApiEvent {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: None,
+            thumbnail: None,
+            category: category.to_string(),
+            is_featured: false,
+            recurring_type: None,
+            created_at: start,
+            updated_at: start,
+        })
+    }
+
+    fn welcome(id: String, title: &str) -> Slide {
+        Slide::Welcome(Welcome {
+            timing: Timing { id, weight: 1, duration: None },
+            title: title.to_string(),
+            subtitle: String::new(),
+        })
+    }
+
+    fn entry(content: PlaylistContent, repeat: u32, duration_seconds: Option<u64>) -> PlaylistEntry {
+        PlaylistEntry { duration_seconds, repeat, content }
+    }
+
+    fn inline(id: String, content: &SlideContent) -> Slide {
+        match content {
+            SlideContent::Welcome { title, .. } => welcome(id, title),
+            other => panic!("unexpected inline slide {:?}", other),
+        }
+    }
+
+    fn ids(slides: &[Slide]) -> Vec<&str> {
+        slides.iter().map(Slide::id).collect()
+    }
+
+    #[test]
+    fn entries_play_in_order_and_repeat() {
+        let entries = [
+            entry(PlaylistContent::Slide { slide: String::from("hello") }, 2, None),
+            entry(PlaylistContent::Events { category: None }, 1, None),
+            entry(PlaylistContent::Inline(SlideContent::Welcome { title: String::from("Bye"), subtitle: String::new() }), 1, None),
+        ];
+        let playlist = Playlist::new("sunday", &entries, &[welcome(String::from("hello"), "Hello")], inline);
+
+        let slides = playlist.slides(&[event("a", "Music"), event("b", "Youth")]);
+        assert_eq!(ids(&slides), ["hello", "hello", "a", "b", "sunday-3"]);
+    }
+
+    #[test]
+    fn events_can_be_limited_to_a_category() {
+        let entries = [entry(PlaylistContent::Events { category: Some(String::from("youth")) }, 1, Some(5))];
+        let playlist = Playlist::new("youth", &entries, &[], inline);
+
+        let slides = playlist.slides(&[event("a", "Music"), event("b", "Youth")]);
+        assert_eq!(ids(&slides), ["b"]);
+        assert_eq!(slides[0].page_duration(0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn unknown_slides_are_skipped() {
+        let entries = [
+            entry(PlaylistContent::Slide { slide: String::from("missing") }, 1, None),
+            entry(PlaylistContent::Events { category: Some(String::new()) }, 1, None),
+        ];
+        let playlist = Playlist::new("main", &entries, &[], inline);
+        assert_eq!(ids(&playlist.slides(&[event("a", "Music")])), ["a"]);
+    }
+}
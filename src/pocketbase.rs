@@ -1,11 +1,17 @@
+use crate::auth::Authenticator;
+use crate::conditional::ConditionalCache;
+use crate::health::RetryAfter;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 const API_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_COLLECTION_PAGES: u32 = 50; // Safety net against a runaway `totalPages`
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiEvent {
     pub id: String,
     pub title: String,
@@ -14,19 +20,123 @@ pub struct ApiEvent {
     pub end_time: DateTime<Utc>,
     pub location: String,
     pub location_url: Option<String>,
+    /// Sign-up or info page; not every backend has this field
+    #[serde(default, alias = "info_url")]
+    pub registration_url: Option<String>,
     pub image: Option<String>,
     pub thumbnail: Option<String>,
     pub category: String,
     pub is_featured: bool,
     pub recurring_type: Option<String>,
+    // Stock PocketBase records name these `created`/`updated`
+    #[serde(alias = "created")]
     pub created_at: DateTime<Utc>,
+    #[serde(alias = "updated")]
     pub updated_at: DateTime<Utc>,
 }
 
+impl ApiEvent {
+    /// Raw PocketBase records store file fields as bare file names, so turn
+    /// them into full `/api/files/...` URLs the image loader can fetch.
+    pub fn resolve_file_urls(&mut self, base_url: &str, collection: &str) {
+        let resolve = |file: Option<String>| -> Option<String> {
+            match file {
+                Some(name) if name.is_empty() => None,
+                Some(name) if name.starts_with("http://") || name.starts_with("https://") => Some(name),
+                Some(name) => Some(format!("{}/api/files/{}/{}/{}", base_url, collection, self.id, name)),
+                None => None,
+            }
+        };
+
+        self.image = resolve(self.image.take());
+        self.thumbnail = resolve(self.thumbnail.take());
+    }
+
+    /// Only web images and `file://` images inside `local_dirs` are kept.
+    /// Anything else, such as a `file://` path from a server or calendar,
+    /// would have the sign read files from its own disk.
+    pub fn drop_non_web_images(&mut self, local_dirs: &[PathBuf]) {
+        for image in [&mut self.image, &mut self.thumbnail] {
+            if let Some(url) = image.as_deref() {
+                let is_web = url.starts_with("http://") || url.starts_with("https://");
+                if !is_web && !is_local_content(url, local_dirs) {
+                    tracing::warn!("Ignoring image '{}' of event '{}': not a web URL or local content", url, self.title);
+                    *image = None;
+                }
+            }
+        }
+    }
+
+    /// Builds an event from a raw PocketBase record, as returned by the records
+    /// API or pushed over the realtime stream.
+    pub fn from_record(mut record: serde_json::Value, base_url: &str, collection: &str) -> Result<Self> {
+        flatten_expanded_relations(&mut record);
+        let mut event: ApiEvent = serde_json::from_value(record)?;
+        event.resolve_file_urls(base_url, collection);
+        Ok(event)
+    }
+}
+
+/// Whether `url` is a `file://` URL inside one of `dirs`, without `..` to
+/// step out of it.
+fn is_local_content(url: &str, dirs: &[PathBuf]) -> bool {
+    let Some(path) = url.strip_prefix("file://").map(Path::new) else {
+        return false;
+    };
+    !path.components().any(|component| component == Component::ParentDir)
+        && dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Replaces relation ids with the display name of the expanded record, so an
+/// `expand=category` query shows "Youth" instead of a record id.
+fn flatten_expanded_relations(record: &mut serde_json::Value) {
+    let Some(expand) = record.get("expand").and_then(|e| e.as_object()).cloned() else {
+        return;
+    };
+
+    for (field, related) in expand {
+        // Multi-relations expand to an array, the first entry is good enough for display
+        let related = match related {
+            serde_json::Value::Array(items) => items.into_iter().next(),
+            other => Some(other),
+        };
+        let name = related.as_ref().and_then(|r| {
+            r.get("name")
+                .or_else(|| r.get("title"))
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+        });
+
+        if let (Some(name), Some(value)) = (name, record.get_mut(&field)) {
+            *value = serde_json::Value::String(name);
+        }
+    }
+}
+
+/// Query against the stock PocketBase records API (`/api/collections/{name}/records`).
+#[derive(Debug, Clone)]
+pub struct CollectionQuery {
+    pub name: String,
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub expand: Option<String>,
+    pub per_page: u32,
+}
+
+#[derive(Debug, Clone)]
+enum Endpoint {
+    /// Our custom `/api/events/upcoming` route with its `{success, data}` envelope
+    Upcoming,
+    Collection(CollectionQuery),
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiClient {
     client: reqwest::Client,
     base_url: String,
+    endpoint: Endpoint,
+    auth: Option<Arc<Authenticator>>,
+    validators: Arc<ConditionalCache>,
 }
 
 impl ApiClient {
@@ -36,17 +146,123 @@ impl ApiClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, base_url }
+        Self {
+            client,
+            base_url,
+            endpoint: Endpoint::Upcoming,
+            auth: None,
+            validators: Arc::new(ConditionalCache::new()),
+        }
+    }
+
+    /// Sends credentials with every request, e.g. to see draft or member-only events.
+    pub fn with_auth(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Reads events from a plain PocketBase collection instead of the custom endpoint.
+    pub fn with_collection(mut self, query: CollectionQuery) -> Self {
+        self.endpoint = Endpoint::Collection(query);
+        self
     }
 
     pub async fn fetch_events(&self) -> Result<Vec<ApiEvent>> {
+        let mut events = match &self.endpoint {
+            Endpoint::Upcoming => self.fetch_upcoming().await?,
+            Endpoint::Collection(query) => self.fetch_collection(query).await?,
+        };
+        for event in &mut events {
+            event.drop_non_web_images(&[]);
+        }
+        Ok(events)
+    }
+
+    /// Sends the request built by `request`, with credentials if configured.
+    /// A 401 drops the cached session and the request is retried once.
+    async fn send(&self, request: impl Fn() -> reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let Some(auth) = &self.auth else {
+            return Ok(request().send().await?);
+        };
+
+        let response = auth.authorize(request()).await?.send().await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED && auth.invalidate().await {
+            tracing::warn!("Request was unauthorized, retrying once with fresh credentials");
+            return Ok(auth.authorize(request()).await?.send().await?);
+        }
+        Ok(response)
+    }
+
+    async fn fetch_collection(&self, query: &CollectionQuery) -> Result<Vec<ApiEvent>> {
+        let url = format!("{}/api/collections/{}/records", self.base_url, query.name);
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RecordPage {
+            page: u32,
+            total_pages: u32,
+            items: Vec<serde_json::Value>,
+        }
+
+        let mut events = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let mut params = vec![
+                ("page", page.to_string()),
+                ("perPage", query.per_page.to_string()),
+            ];
+            if let Some(filter) = &query.filter {
+                params.push(("filter", filter.clone()));
+            }
+            if let Some(sort) = &query.sort {
+                params.push(("sort", sort.clone()));
+            }
+            if let Some(expand) = &query.expand {
+                params.push(("expand", expand.clone()));
+            }
+
+            // Validators are stored per page URL, so build the full URL up front
+            let page_url = reqwest::Url::parse_with_params(&url, &params)?.to_string();
+
+            tracing::info!("Fetching page {} of collection '{}' from URL: {}", page, query.name, url);
+            let response = self.send(|| self.validators.prepare(&page_url, self.client.get(&page_url)))
+                .await
+                .map_err(|e| {
+                    tracing::error!("HTTP request failed: {}", e);
+                    e
+                })
+                .and_then(check_status)?;
+
+            let (body, _) = self.validators.body(&page_url, response).await?;
+            let records: RecordPage = serde_json::from_slice(&body).map_err(|e| {
+                tracing::error!("Failed to parse JSON response: {}", e);
+                e
+            })?;
+
+            for record in records.items {
+                match ApiEvent::from_record(record, &self.base_url, &query.name) {
+                    Ok(event) => events.push(event),
+                    Err(e) => tracing::warn!("Skipping record that is not a valid event: {}", e),
+                }
+            }
+
+            if records.page >= records.total_pages || page >= MAX_COLLECTION_PAGES {
+                break;
+            }
+            page += 1;
+        }
+
+        tracing::info!("Successfully parsed {} events from collection '{}'", events.len(), query.name);
+        Ok(events)
+    }
+
+    async fn fetch_upcoming(&self) -> Result<Vec<ApiEvent>> {
         let url = format!("{}/api/events/upcoming", self.base_url);
         tracing::info!("Fetching events from URL: {}", url);
         
-        let response = match self.client.get(&url)
-            .header("Cache-Control", "max-age=60") // Cache for 60 seconds
-            .send()
-            .await 
+        let response = match self.send(|| self.validators.prepare(&url, self.client.get(&url)))
+        .await
         {
             Ok(resp) => {
                 tracing::info!("Got response with status: {}", resp.status());
@@ -54,17 +270,15 @@ impl ApiClient {
             },
             Err(e) => {
                 tracing::error!("HTTP request failed: {}", e);
-                return Err(e.into());
+                return Err(e);
             }
         };
 
-        let response = match response.error_for_status() {
-            Ok(resp) => resp,
-            Err(e) => {
-                tracing::error!("HTTP error status: {}", e);
-                return Err(e.into());
-            }
-        };
+        let response = check_status(response)?;
+        let (body, modified) = self.validators.body(&url, response).await?;
+        if !modified {
+            tracing::info!("Events unchanged since last fetch");
+        }
 
         #[derive(Deserialize)]
         struct ApiResponse {
@@ -72,7 +286,7 @@ impl ApiClient {
             data: Vec<ApiEvent>,
         }
 
-        match response.json().await {
+        match serde_json::from_slice(&body) {
             Ok(api_response) => {
                 let ApiResponse { success, data } = api_response;
                 if success {
@@ -89,4 +303,89 @@ impl ApiClient {
             }
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Turns an error status into an error, keeping any `Retry-After` hint for the backoff.
+fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let retry_after = RetryAfter::from_response(&response);
+    response.error_for_status().map_err(|e| {
+        tracing::error!("HTTP error status: {}", e);
+        RetryAfter::attach(e, retry_after)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(extra: serde_json::Value) -> serde_json::Value {
+        let mut record = serde_json::json!({
+            "id": "rec1",
+            "title": "Potluck",
+            "description": "",
+            "start_time": "2030-01-01T18:00:00Z",
+            "end_time": "2030-01-01T20:00:00Z",
+            "location": "Fellowship hall",
+            "category": "cat1",
+            "is_featured": false,
+            "created_at": "2029-12-01T00:00:00Z",
+            "updated_at": "2029-12-01T00:00:00Z",
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            record[key] = value.clone();
+        }
+        record
+    }
+
+    #[test]
+    fn expanded_relations_show_their_name() {
+        let single = record(serde_json::json!({ "expand": { "category": { "id": "cat1", "name": "Youth" } } }));
+        let event = ApiEvent::from_record(single, "http://pb", "events").unwrap();
+        assert_eq!(event.category, "Youth");
+
+        let multiple = record(serde_json::json!({
+            "expand": { "category": [{ "id": "cat1", "title": "Music" }, { "id": "cat2", "title": "Youth" }] },
+        }));
+        let event = ApiEvent::from_record(multiple, "http://pb", "events").unwrap();
+        assert_eq!(event.category, "Music");
+    }
+
+    #[test]
+    fn file_names_become_urls() {
+        let event = ApiEvent::from_record(
+            record(serde_json::json!({ "image": "poster.png", "thumbnail": "https://cdn.example/thumb.png" })),
+            "http://pb",
+            "events",
+        )
+        .unwrap();
+        assert_eq!(event.image.as_deref(), Some("http://pb/api/files/events/rec1/poster.png"));
+        assert_eq!(event.thumbnail.as_deref(), Some("https://cdn.example/thumb.png"));
+
+        let event = ApiEvent::from_record(record(serde_json::json!({ "image": "" })), "http://pb", "events").unwrap();
+        assert_eq!(event.image, None);
+    }
+
+    #[test]
+    fn only_web_images_and_local_content_are_kept() {
+        let mut event = ApiEvent::from_record(record(serde_json::json!({})), "http://pb", "events").unwrap();
+        event.image = Some(String::from("https://cdn.example/a.png"));
+        event.thumbnail = Some(String::from("file:///etc/passwd"));
+        event.drop_non_web_images(&[]);
+        assert_eq!(event.image.as_deref(), Some("https://cdn.example/a.png"));
+        assert_eq!(event.thumbnail, None);
+
+        let content = [PathBuf::from("/srv/content")];
+        event.image = Some(String::from("file:///srv/content/choir.png"));
+        event.thumbnail = Some(String::from("file:///srv/content/../secrets.png"));
+        event.drop_non_web_images(&content);
+        assert_eq!(event.image.as_deref(), Some("file:///srv/content/choir.png"));
+        assert_eq!(event.thumbnail, None);
+    }
+
+    #[test]
+    fn records_missing_fields_are_rejected() {
+        let mut incomplete = record(serde_json::json!({}));
+        incomplete.as_object_mut().unwrap().remove("start_time");
+        assert!(ApiEvent::from_record(incomplete, "http://pb", "events").is_err());
+    }
+}
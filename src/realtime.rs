@@ -0,0 +1,327 @@
+use crate::auth::Authenticator;
+use crate::pocketbase::ApiEvent;
+use anyhow::Result;
+use futures::Stream;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+
+/// Changes pushed by PocketBase's `/api/realtime` endpoint.
+#[derive(Debug, Clone)]
+pub enum RealtimeEvent {
+    Connected,
+    Created(ApiEvent),
+    Updated(ApiEvent),
+    Deleted(String),
+    Disconnected(String),
+}
+
+#[derive(Deserialize)]
+struct ConnectData {
+    #[serde(rename = "clientId")]
+    client_id: String,
+}
+
+#[derive(Deserialize)]
+struct RecordMessage {
+    action: String,
+    record: serde_json::Value,
+}
+
+struct SseFrame {
+    event: String,
+    data: String,
+}
+
+struct Listener {
+    client: reqwest::Client,
+    base_url: String,
+    collection: String,
+    auth: Option<Arc<Authenticator>>,
+    response: Option<reqwest::Response>,
+    buffer: Vec<u8>,
+    pending: VecDeque<RealtimeEvent>,
+    attempted: bool,
+}
+
+/// Subscribes to record changes in `collection`, reconnecting forever.
+///
+/// Every (re)connection yields `Connected` and every drop yields
+/// `Disconnected`, so the caller knows when to fall back to polling.
+/// PocketBase checks record access rules against the credentials sent
+/// with the subscription request, so `auth` decides which events arrive.
+pub fn subscribe(
+    base_url: String,
+    collection: String,
+    auth: Option<Arc<Authenticator>>,
+) -> impl Stream<Item = RealtimeEvent> {
+    // No overall request timeout here, the stream is meant to stay open
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .expect("Failed to create HTTP client");
+
+    let listener = Listener {
+        client,
+        base_url,
+        collection,
+        auth,
+        response: None,
+        buffer: Vec::new(),
+        pending: VecDeque::new(),
+        attempted: false,
+    };
+
+    futures::stream::unfold(listener, |mut listener| async move {
+        let event = listener.next_event().await;
+        Some((event, listener))
+    })
+}
+
+impl Listener {
+    async fn next_event(&mut self) -> RealtimeEvent {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+
+            let Some(mut response) = self.response.take() else {
+                if self.attempted {
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+                self.attempted = true;
+
+                match self.connect().await {
+                    Ok(response) => {
+                        self.response = Some(response);
+                        self.buffer.clear();
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::error!("Realtime connection failed: {}", e);
+                        return RealtimeEvent::Disconnected(e.to_string());
+                    }
+                }
+            };
+
+            match response.chunk().await {
+                Ok(Some(bytes)) => {
+                    self.buffer.extend(bytes.iter().filter(|b| **b != b'\r'));
+                    self.response = Some(response);
+
+                    while let Some(frame) = self.take_frame() {
+                        if let Err(e) = self.handle_frame(frame).await {
+                            tracing::error!("Realtime subscription failed: {}", e);
+                            self.response = None;
+                            return RealtimeEvent::Disconnected(e.to_string());
+                        }
+                    }
+                }
+                Ok(None) => {
+                    tracing::warn!("Realtime stream closed by server");
+                    return RealtimeEvent::Disconnected(String::from("stream closed by server"));
+                }
+                Err(e) => {
+                    tracing::error!("Realtime stream error: {}", e);
+                    return RealtimeEvent::Disconnected(e.to_string());
+                }
+            }
+        }
+    }
+
+    async fn connect(&self) -> Result<reqwest::Response> {
+        let url = format!("{}/api/realtime", self.base_url);
+        tracing::info!("Opening realtime stream: {}", url);
+
+        let response = self.client.get(&url)
+            .header("Accept", "text/event-stream")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(response)
+    }
+
+    fn take_frame(&mut self) -> Option<SseFrame> {
+        let end = self.buffer.windows(2).position(|w| w == b"\n\n")?;
+        let raw: Vec<u8> = self.buffer.drain(..end + 2).collect();
+        let raw = String::from_utf8_lossy(&raw);
+
+        let mut frame = SseFrame { event: String::from("message"), data: String::new() };
+        for line in raw.lines() {
+            if let Some(value) = line.strip_prefix("event:") {
+                frame.event = value.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !frame.data.is_empty() {
+                    frame.data.push('\n');
+                }
+                frame.data.push_str(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        Some(frame)
+    }
+
+    async fn handle_frame(&mut self, frame: SseFrame) -> Result<()> {
+        if frame.event == "PB_CONNECT" {
+            let data: ConnectData = serde_json::from_str(&frame.data)?;
+            self.set_subscriptions(&data.client_id).await?;
+            tracing::info!("Realtime subscription active for collection '{}'", self.collection);
+            self.pending.push_back(RealtimeEvent::Connected);
+            return Ok(());
+        }
+
+        if frame.event != self.collection && !frame.event.starts_with(&format!("{}/", self.collection)) {
+            return Ok(());
+        }
+
+        let message: RecordMessage = match serde_json::from_str(&frame.data) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Ignoring malformed realtime message: {}", e);
+                return Ok(());
+            }
+        };
+
+        if message.action == "delete" {
+            if let Some(id) = message.record.get("id").and_then(|id| id.as_str()) {
+                tracing::info!("Realtime delete for event {}", id);
+                self.pending.push_back(RealtimeEvent::Deleted(id.to_string()));
+            }
+            return Ok(());
+        }
+
+        let event = match ApiEvent::from_record(message.record, &self.base_url, &self.collection) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Ignoring realtime record that is not an event: {}", e);
+                return Ok(());
+            }
+        };
+
+        tracing::info!("Realtime {} for event {}", message.action, event.id);
+        match message.action.as_str() {
+            "create" => self.pending.push_back(RealtimeEvent::Created(event)),
+            "update" => self.pending.push_back(RealtimeEvent::Updated(event)),
+            other => tracing::warn!("Ignoring unknown realtime action: {}", other),
+        }
+        Ok(())
+    }
+
+    async fn set_subscriptions(&self, client_id: &str) -> Result<()> {
+        let url = format!("{}/api/realtime", self.base_url);
+
+        // Older PocketBase releases use the bare collection name as the topic, newer
+        // ones use `collection/*`. Asking for both is harmless since applying the
+        // same record twice is idempotent.
+        let body = serde_json::json!({
+            "clientId": client_id,
+            "subscriptions": [self.collection.clone(), format!("{}/*", self.collection)],
+        });
+
+        let request = || self.client.post(&url).timeout(CONNECT_TIMEOUT).json(&body);
+
+        let Some(auth) = &self.auth else {
+            request().send().await?.error_for_status()?;
+            return Ok(());
+        };
+
+        let response = auth.authorize(request()).await?.send().await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED && auth.invalidate().await {
+            auth.authorize(request()).await?.send().await?.error_for_status()?;
+        } else {
+            response.error_for_status()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener(buffer: &str) -> Listener {
+        Listener {
+            client: reqwest::Client::new(),
+            base_url: String::from("http://sign.example"),
+            collection: String::from("events"),
+            auth: None,
+            response: None,
+            buffer: buffer.as_bytes().to_vec(),
+            pending: VecDeque::new(),
+            attempted: false,
+        }
+    }
+
+    fn record_frame(event: &str, action: &str, id: &str) -> SseFrame {
+        let data = serde_json::json!({
+            "action": action,
+            "record": {
+                "id": id,
+                "title": "Choir practice",
+                "description": "",
+                "start_time": "2030-01-01T18:00:00Z",
+                "end_time": "2030-01-01T19:00:00Z",
+                "location": "Hall",
+                "image": "poster.png",
+                "category": "Music",
+                "is_featured": false,
+                "created_at": "2029-12-01T00:00:00Z",
+                "updated_at": "2029-12-01T00:00:00Z",
+            },
+        });
+        SseFrame { event: event.to_string(), data: data.to_string() }
+    }
+
+    #[test]
+    fn frames_are_split_on_blank_lines() {
+        let mut listener = listener("event: PB_CONNECT\ndata: {\"a\":\ndata: 1}\n\ndata: second\n\nevent: partial\n");
+
+        let first = listener.take_frame().unwrap();
+        assert_eq!(first.event, "PB_CONNECT");
+        assert_eq!(first.data, "{\"a\":\n1}");
+
+        let second = listener.take_frame().unwrap();
+        assert_eq!(second.event, "message");
+        assert_eq!(second.data, "second");
+
+        // The rest waits for more bytes
+        assert!(listener.take_frame().is_none());
+        assert_eq!(listener.buffer, b"event: partial\n");
+    }
+
+    #[test]
+    fn record_changes_become_events() {
+        let mut listener = listener("");
+        for frame in [
+            record_frame("events/*", "create", "a"),
+            record_frame("events", "update", "b"),
+            record_frame("events/c", "delete", "c"),
+        ] {
+            futures::executor::block_on(listener.handle_frame(frame)).unwrap();
+        }
+
+        let events: Vec<_> = listener.pending.drain(..).collect();
+        assert!(matches!(&events[0], RealtimeEvent::Created(event)
+            if event.id == "a" && event.image.as_deref() == Some("http://sign.example/api/files/events/a/poster.png")));
+        assert!(matches!(&events[1], RealtimeEvent::Updated(event) if event.id == "b"));
+        assert!(matches!(&events[2], RealtimeEvent::Deleted(id) if id == "c"));
+    }
+
+    #[test]
+    fn other_collections_and_bad_messages_are_ignored() {
+        let mut listener = listener("");
+        let frames = [
+            record_frame("announcements", "create", "a"),
+            record_frame("eventsarchive", "create", "b"),
+            SseFrame { event: String::from("events"), data: String::from("not json") },
+        ];
+        for frame in frames {
+            futures::executor::block_on(listener.handle_frame(frame)).unwrap();
+        }
+        assert!(listener.pending.is_empty());
+    }
+}
@@ -0,0 +1,482 @@
+use crate::pocketbase::ApiEvent;
+use crate::timezone;
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+// Stops rules that rarely match (e.g. the 31st of every month for a century) from spinning forever
+const MAX_PERIODS: u32 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The subset of an RFC 5545 RRULE Beacon knows how to expand.
+///
+/// Occurrences are computed in wall-clock time, so a 10:00 service stays at
+/// 10:00 across daylight saving changes; callers convert each one to UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    /// Weekdays, optionally with an ordinal (`2SU` = second Sunday, `-1FR` = last Friday)
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month, negative values count from the end
+    pub by_month_day: Vec<i32>,
+}
+
+impl Rule {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+        }
+    }
+
+    /// Parses an RRULE value such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`.
+    ///
+    /// `parse_until` turns the UNTIL value into wall-clock time, since that
+    /// depends on the time zone the caller is working in.
+    pub fn parse(value: &str, parse_until: impl Fn(&str) -> Option<NaiveDateTime>) -> Result<Self> {
+        let mut frequency = None;
+        let mut rule = Rule::new(Frequency::Daily);
+
+        for part in value.split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Malformed RRULE part: {}", part))?;
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(anyhow::anyhow!("Unsupported RRULE frequency: {}", other)),
+                    })
+                }
+                "INTERVAL" => rule.interval = val.parse::<u32>()?.max(1),
+                "COUNT" => rule.count = Some(val.parse()?),
+                "UNTIL" => {
+                    rule.until = Some(parse_until(val)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid RRULE UNTIL: {}", val))?)
+                }
+                "BYDAY" => {
+                    rule.by_day = val
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<Vec<_>>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = val
+                        .split(',')
+                        .map(|d| d.parse::<i32>().map_err(Into::into))
+                        .collect::<Result<Vec<_>>>()?
+                }
+                // WKST, BYSETPOS and friends are rare in church calendars
+                other => tracing::warn!("Ignoring unsupported RRULE part: {}", other),
+            }
+        }
+
+        rule.frequency = frequency.ok_or_else(|| anyhow::anyhow!("RRULE without FREQ: {}", value))?;
+        Ok(rule)
+    }
+
+    /// Maps an event's `recurring_type` to a rule anchored on its first start.
+    /// Returns `None` for one-off events and for types we don't recognise.
+    pub fn from_recurring_type(value: &str, start: NaiveDateTime) -> Option<Self> {
+        let normalized = value.trim().to_ascii_lowercase().replace(['-', ' '], "_");
+        let rule = match normalized.as_str() {
+            "" | "none" | "once" => return None,
+            "daily" => Rule::new(Frequency::Daily),
+            "weekly" => Rule::new(Frequency::Weekly),
+            "biweekly" | "bi_weekly" | "every_other_week" => Rule {
+                interval: 2,
+                ..Rule::new(Frequency::Weekly)
+            },
+            "monthly" | "monthly_date" | "monthly_by_date" => Rule::new(Frequency::Monthly),
+            "monthly_weekday" | "monthly_by_weekday" | "monthly_by_day" => {
+                // The 5th Tuesday only exists some months, so treat it as "last Tuesday"
+                let nth = match (start.day() as i32 - 1) / 7 + 1 {
+                    5 => -1,
+                    nth => nth,
+                };
+                Rule {
+                    by_day: vec![(Some(nth), start.weekday())],
+                    ..Rule::new(Frequency::Monthly)
+                }
+            }
+            "yearly" | "annually" => Rule::new(Frequency::Yearly),
+            other => {
+                tracing::warn!("Unsupported recurring_type '{}', showing the event once", other);
+                return None;
+            }
+        };
+        Some(rule)
+    }
+
+    /// Every occurrence of the series that begins at `start` falling between
+    /// `from` and `horizon`.
+    ///
+    /// Without a COUNT, periods before `from` are skipped rather than walked
+    /// through, so long-running series cost the same as new ones.
+    pub fn occurrences(&self, start: NaiveDateTime, from: NaiveDateTime, horizon: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut occurrences = Vec::new();
+        let interval = self.interval.max(1);
+        // COUNT is counted from the first occurrence, so those series can't skip ahead
+        let (first, limit) = match self.count {
+            Some(count) => (0, MAX_PERIODS.max(count)),
+            None => ((self.periods_between(start, from) / interval).saturating_sub(1), MAX_PERIODS),
+        };
+        let mut seen = 0;
+
+        for period in first..first.saturating_add(limit) {
+            let mut candidates = self.candidates(start, period * interval);
+            candidates.sort();
+
+            for candidate in candidates {
+                if candidate < start {
+                    continue;
+                }
+                if candidate > horizon || self.until.map_or(false, |until| candidate > until) {
+                    return occurrences;
+                }
+                if self.count.map_or(false, |count| seen >= count) {
+                    return occurrences;
+                }
+                seen += 1;
+                if candidate >= from {
+                    occurrences.push(candidate);
+                }
+            }
+        }
+
+        occurrences
+    }
+
+    /// Whole days, weeks, months or years from `start` to `until`, by the rule's frequency.
+    fn periods_between(&self, start: NaiveDateTime, until: NaiveDateTime) -> u32 {
+        let week = |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        let periods = match self.frequency {
+            Frequency::Daily => (until.date() - start.date()).num_days(),
+            Frequency::Weekly => (week(until.date()) - week(start.date())).num_weeks(),
+            Frequency::Monthly => {
+                (until.year() as i64 * 12 + until.month0() as i64) - (start.year() as i64 * 12 + start.month0() as i64)
+            }
+            Frequency::Yearly => (until.year() - start.year()) as i64,
+        };
+        u32::try_from(periods.max(0)).unwrap_or(u32::MAX)
+    }
+
+    fn candidates(&self, start: NaiveDateTime, offset: u32) -> Vec<NaiveDateTime> {
+        let time = start.time();
+        let offset = offset as i64;
+
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.date() + Duration::days(offset);
+                if self.by_day.is_empty() || self.by_day.iter().any(|(_, wd)| *wd == date.weekday()) {
+                    vec![date.and_time(time)]
+                } else {
+                    vec![]
+                }
+            }
+            Frequency::Weekly => {
+                let week_start = start.date()
+                    - Duration::days(start.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(offset);
+
+                if self.by_day.is_empty() {
+                    vec![(week_start + Duration::days(start.weekday().num_days_from_monday() as i64)).and_time(time)]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|(_, wd)| (week_start + Duration::days(wd.num_days_from_monday() as i64)).and_time(time))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + offset;
+                let (year, month) = ((months / 12) as i32, (months % 12) as u32 + 1);
+
+                let dates: Vec<NaiveDate> = if !self.by_month_day.is_empty() {
+                    self.by_month_day
+                        .iter()
+                        .filter_map(|day| month_day(year, month, *day))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    self.by_day
+                        .iter()
+                        .flat_map(|(nth, wd)| weekdays_in_month(year, month, *nth, *wd))
+                        .collect()
+                } else {
+                    NaiveDate::from_ymd_opt(year, month, start.day()).into_iter().collect()
+                };
+
+                dates.into_iter().map(|date| date.and_time(time)).collect()
+            }
+            Frequency::Yearly => {
+                NaiveDate::from_ymd_opt(start.year() + offset as i32, start.month(), start.day())
+                    .map(|date| vec![date.and_time(time)])
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+fn parse_by_day(value: &str) -> Result<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    if value.len() < 2 {
+        return Err(anyhow::anyhow!("Invalid BYDAY value: {}", value));
+    }
+
+    let (nth, day) = value.split_at(value.len() - 2);
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        other => return Err(anyhow::anyhow!("Invalid BYDAY weekday: {}", other)),
+    };
+    let nth = if nth.is_empty() { None } else { Some(nth.trim_start_matches('+').parse()?) };
+    Ok((nth, weekday))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28)
+}
+
+fn month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let len = days_in_month(year, month) as i32;
+    let day = if day < 0 { len + day + 1 } else { day };
+    if day < 1 || day > len {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month, day as u32)
+}
+
+/// All `weekday`s in the month, or only the `nth` one (negative counts from the end).
+pub fn weekdays_in_month(year: i32, month: u32, nth: Option<i32>, weekday: Weekday) -> Vec<NaiveDate> {
+    let all: Vec<NaiveDate> = (1..=days_in_month(year, month))
+        .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .filter(|date| date.weekday() == weekday)
+        .collect();
+
+    match nth {
+        None => all,
+        Some(n) if n > 0 => all.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) if n < 0 => all.len().checked_sub(n.unsigned_abs() as usize)
+            .and_then(|i| all.get(i).copied())
+            .into_iter()
+            .collect(),
+        Some(_) => vec![],
+    }
+}
+
+/// Replaces a recurring event with its instances that haven't ended yet and
+/// start within `horizon_days`. Each instance gets the stable id
+/// `<event id>@<start timestamp>`; one-off events are returned unchanged.
+pub fn expand_event(event: ApiEvent, horizon_days: u32, tz: Tz) -> Vec<ApiEvent> {
+    let local_start = timezone::utc_to_local(event.start_time, tz);
+    let Some(rule) = event
+        .recurring_type
+        .as_deref()
+        .and_then(|value| Rule::from_recurring_type(value, local_start))
+    else {
+        return vec![event];
+    };
+
+    // The backend has no zone of its own, so the series repeats in the church's zone
+    let now = Utc::now();
+    let horizon = timezone::utc_to_local(now + Duration::days(horizon_days as i64), tz);
+    let length = event.end_time - event.start_time;
+
+    let instances: Vec<ApiEvent> = rule
+        .occurrences(local_start, timezone::utc_to_local(now, tz) - length, horizon)
+        .into_iter()
+        .map(|occurrence| timezone::local_to_utc(occurrence, tz))
+        .filter(|start| *start + length > now)
+        .map(|start| ApiEvent {
+            id: format!("{}@{}", event.id, start.timestamp()),
+            start_time: start,
+            end_time: start + length,
+            // Instances are concrete, so expanding them again is a no-op
+            recurring_type: None,
+            ..event.clone()
+        })
+        .collect();
+
+    tracing::debug!("Expanded recurring event '{}' into {} instances", event.title, instances.len());
+    instances
+}
+
+pub fn expand_events(events: Vec<ApiEvent>, horizon_days: u32, tz: Tz) -> Vec<ApiEvent> {
+    events
+        .into_iter()
+        .flat_map(|event| expand_event(event, horizon_days, tz))
+        .collect()
+}
+
+/// Whether `id` is the event `master_id` itself or one of its expanded instances.
+pub fn is_same_series(id: &str, master_id: &str) -> bool {
+    id.strip_prefix(master_id)
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with('@'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn rule(value: &str) -> Rule {
+        Rule::parse(value, |until| NaiveDateTime::parse_from_str(until, "%Y%m%dT%H%M%S").ok()).unwrap()
+    }
+
+    #[test]
+    fn parses_rule_parts() {
+        let parsed = rule("FREQ=MONTHLY;INTERVAL=2;COUNT=5;BYDAY=2SU,-1FR;WKST=SU");
+        assert_eq!(parsed.frequency, Frequency::Monthly);
+        assert_eq!(parsed.interval, 2);
+        assert_eq!(parsed.count, Some(5));
+        assert_eq!(parsed.by_day, vec![(Some(2), Weekday::Sun), (Some(-1), Weekday::Fri)]);
+
+        assert!(Rule::parse("INTERVAL=2", |_| None).is_err());
+        assert!(Rule::parse("FREQ=HOURLY", |_| None).is_err());
+        assert!(Rule::parse("FREQ=DAILY;UNTIL=soon", |_| None).is_err());
+    }
+
+    #[test]
+    fn weekly_by_day_within_until() {
+        let occurrences = rule("FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20250115T235959")
+            .occurrences(at("2025-01-06 19:00"), at("2025-01-01 00:00"), at("2025-12-31 00:00"));
+        assert_eq!(
+            occurrences,
+            vec![at("2025-01-06 19:00"), at("2025-01-08 19:00"), at("2025-01-13 19:00"), at("2025-01-15 19:00")]
+        );
+    }
+
+    #[test]
+    fn monthly_by_ordinal_weekday_and_month_day() {
+        let from = at("2025-01-01 00:00");
+        let horizon = at("2025-03-31 23:00");
+        assert_eq!(
+            rule("FREQ=MONTHLY;BYDAY=-1FR").occurrences(at("2025-01-31 18:00"), from, horizon),
+            vec![at("2025-01-31 18:00"), at("2025-02-28 18:00"), at("2025-03-28 18:00")]
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;BYMONTHDAY=-1").occurrences(at("2025-01-31 09:00"), from, horizon),
+            vec![at("2025-01-31 09:00"), at("2025-02-28 09:00"), at("2025-03-31 09:00")]
+        );
+    }
+
+    #[test]
+    fn old_series_still_has_upcoming_occurrences() {
+        let occurrences = rule("FREQ=DAILY")
+            .occurrences(at("1990-01-01 09:00"), at("2025-06-01 00:00"), at("2025-06-03 23:00"));
+        assert_eq!(occurrences, vec![at("2025-06-01 09:00"), at("2025-06-02 09:00"), at("2025-06-03 09:00")]);
+    }
+
+    #[test]
+    fn count_includes_past_occurrences() {
+        let occurrences = rule("FREQ=WEEKLY;COUNT=4")
+            .occurrences(at("2025-01-06 10:00"), at("2025-01-20 00:00"), at("2025-12-31 00:00"));
+        assert_eq!(occurrences, vec![at("2025-01-20 10:00"), at("2025-01-27 10:00")]);
+    }
+
+    #[test]
+    fn interval_stays_aligned_to_start() {
+        let occurrences = rule("FREQ=WEEKLY;INTERVAL=2")
+            .occurrences(at("2025-01-06 10:00"), at("2025-03-01 00:00"), at("2025-03-20 00:00"));
+        assert_eq!(occurrences, vec![at("2025-03-03 10:00"), at("2025-03-17 10:00")]);
+    }
+
+    #[test]
+    fn wall_clock_time_is_kept_across_dst() {
+        let rule = Rule::parse("FREQ=WEEKLY", |_| None).unwrap();
+        let occurrences = rule.occurrences(at("2025-03-02 10:00"), at("2025-03-01 00:00"), at("2025-03-16 23:00"));
+        assert_eq!(occurrences, vec![at("2025-03-02 10:00"), at("2025-03-09 10:00"), at("2025-03-16 10:00")]);
+
+        let tz = chrono_tz::America::New_York;
+        let utc: Vec<_> = occurrences.into_iter().map(|o| timezone::local_to_utc(o, tz).naive_utc()).collect();
+        assert_eq!(utc, vec![at("2025-03-02 15:00"), at("2025-03-09 14:00"), at("2025-03-16 14:00")]);
+    }
+
+    #[test]
+    fn recurring_types_map_to_rules() {
+        let start = at("2025-03-30 10:00");
+        assert_eq!(Rule::from_recurring_type("none", start), None);
+        assert_eq!(Rule::from_recurring_type("fortnightly", start), None);
+
+        let biweekly = Rule::from_recurring_type("Every-Other Week", start).unwrap();
+        assert_eq!((biweekly.frequency, biweekly.interval), (Frequency::Weekly, 2));
+
+        // March 30th is the fifth Sunday, which becomes the last one
+        let monthly = Rule::from_recurring_type("monthly_weekday", start).unwrap();
+        assert_eq!(monthly.by_day, vec![(Some(-1), Weekday::Sun)]);
+    }
+
+    #[test]
+    fn recurring_event_becomes_upcoming_instances() {
+        let start = chrono::DateTime::parse_from_rfc3339("2020-01-05T15:00:00Z").unwrap().with_timezone(&Utc);
+        let event = ApiEvent {
+            id: String::from("service"),
+            title: String::from("Sunday service"),
+            description: String::new(),
+            start_time: start,
+            end_time: start + Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: None,
+            thumbnail: None,
+            category: String::new(),
+            is_featured: false,
+            recurring_type: Some(String::from("weekly")),
+            created_at: start,
+            updated_at: start,
+        };
+        let tz = chrono_tz::America::New_York;
+
+        let instances = expand_event(event.clone(), 21, tz);
+        assert!(instances.len() >= 3);
+        for instance in &instances {
+            assert!(is_same_series(&instance.id, "service"));
+            assert_eq!(instance.id, format!("service@{}", instance.start_time.timestamp()));
+            assert_eq!(instance.recurring_type, None);
+            assert!(instance.end_time > Utc::now());
+            let local = timezone::utc_to_local(instance.start_time, tz);
+            assert_eq!((local.weekday(), local.time()), (Weekday::Sun, at("2020-01-05 10:00").time()));
+        }
+
+        let once = ApiEvent { recurring_type: None, ..event };
+        assert_eq!(expand_event(once, 21, tz).len(), 1);
+    }
+
+    #[test]
+    fn series_ids_match_their_instances() {
+        assert!(is_same_series("service", "service"));
+        assert!(is_same_series("service@1736085600", "service"));
+        assert!(!is_same_series("service-evening", "service"));
+    }
+}
@@ -0,0 +1,463 @@
+use html2text::render::text_renderer::{TaggedLine, TextDecorator};
+
+// Wide enough that html2text never wraps; the renderer wraps to the real width
+const UNWRAPPED_WIDTH: usize = 10_000;
+// Private-use character html2text puts in front of headings, so we can find them again
+const HEADING_MARKER: char = '\u{E000}';
+
+/// A run of text with one style.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub strong: bool,
+    pub emphasis: bool,
+    /// Link text; only the text is shown since nobody can click a sign
+    pub link: bool,
+    pub code: bool,
+}
+
+impl Span {
+    fn same_style(&self, other: &Span) -> bool {
+        self.strong == other.strong
+            && self.emphasis == other.emphasis
+            && self.link == other.link
+            && self.code == other.code
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Text,
+    Heading,
+    /// Includes its bullet or number at the start of the text
+    ListItem,
+    /// Space between paragraphs
+    Blank,
+}
+
+/// One line of a description as the editor wrote it, before wrapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub kind: LineKind,
+    pub spans: Vec<Span>,
+}
+
+impl Line {
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// Parses an event description written as HTML, Markdown or plain text.
+/// Paragraphs, headings, lists, line breaks and emphasis are kept.
+pub fn parse(description: &str) -> Vec<Line> {
+    let html = if looks_like_html(description) {
+        // html2text treats <strong> as bold but not the older <b>
+        bold_to_strong(description)
+    } else {
+        markdown_to_html(description)
+    };
+
+    let tagged = match html2text::config::with_decorator(Decorator).lines_from_read(html.as_bytes(), UNWRAPPED_WIDTH) {
+        Ok(lines) => lines,
+        Err(e) => {
+            tracing::warn!("Could not render description, showing it as plain text: {}", e);
+            return plain_lines(description);
+        }
+    };
+
+    let mut lines: Vec<Line> = tagged.iter().map(convert_line).collect();
+
+    // Collapse runs of blank lines and drop them at the edges
+    lines.dedup_by(|b, a| a.kind == LineKind::Blank && b.kind == LineKind::Blank);
+    while lines.first().map_or(false, |line| line.kind == LineKind::Blank) {
+        lines.remove(0);
+    }
+    while lines.last().map_or(false, |line| line.kind == LineKind::Blank) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Plain text of a parsed description, e.g. for counting words.
+pub fn plain_text(lines: &[Line]) -> String {
+    lines
+        .iter()
+        .map(Line::text)
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn plain_lines(description: &str) -> Vec<Line> {
+    description
+        .lines()
+        .map(|text| Line {
+            kind: if text.trim().is_empty() { LineKind::Blank } else { LineKind::Text },
+            spans: vec![Span { text: text.trim().to_string(), ..Default::default() }],
+        })
+        .collect()
+}
+
+fn convert_line(tagged: &TaggedLine<Vec<Mark>>) -> Line {
+    let mut spans: Vec<Span> = Vec::new();
+    for piece in tagged.tagged_strings() {
+        let span = Span {
+            text: piece.s.clone(),
+            strong: piece.tag.contains(&Mark::Strong),
+            emphasis: piece.tag.contains(&Mark::Emphasis),
+            link: piece.tag.contains(&Mark::Link),
+            code: piece.tag.contains(&Mark::Code),
+        };
+        match spans.last_mut() {
+            Some(last) if last.same_style(&span) => last.text.push_str(&span.text),
+            _ => spans.push(span),
+        }
+    }
+
+    // Sub-blocks like list items are indented with spaces; the view does its own indenting
+    let mut kind = LineKind::Text;
+    if let Some(first) = spans.first_mut() {
+        let trimmed = first.text.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(HEADING_MARKER) {
+            kind = LineKind::Heading;
+            first.text = rest.trim_start_matches(HEADING_MARKER).to_string();
+        } else {
+            if trimmed.starts_with("• ") || starts_with_number(trimmed) {
+                kind = LineKind::ListItem;
+            }
+            first.text = trimmed.to_string();
+        }
+    }
+    if let Some(last) = spans.last_mut() {
+        last.text.truncate(last.text.trim_end().len());
+    }
+    spans.retain(|span| !span.text.is_empty());
+
+    if spans.is_empty() {
+        kind = LineKind::Blank;
+    }
+    Line { kind, spans }
+}
+
+fn starts_with_number(text: &str) -> bool {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && text[digits..].starts_with(". ")
+}
+
+fn looks_like_html(text: &str) -> bool {
+    text.match_indices('<').any(|(index, _)| {
+        let rest = &text[index + 1..];
+        rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') && rest.contains('>')
+    })
+}
+
+/// Renames `<b>` tags to `<strong>` in any case, keeping their attributes.
+fn bold_to_strong(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        result.push_str(&rest[..=open]);
+        rest = &rest[open + 1..];
+        if let Some(closing) = rest.strip_prefix('/') {
+            result.push('/');
+            rest = closing;
+        }
+        let is_bold = rest.starts_with(['b', 'B'])
+            && matches!(rest[1..].chars().next(), Some(c) if c == '>' || c == '/' || c.is_ascii_whitespace());
+        if is_bold {
+            result.push_str("strong");
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Converts the bits of Markdown people actually type into an event form:
+/// `#` headings, `-`/`*`/`1.` lists, blank lines between paragraphs,
+/// `**bold**`, `*italic*`/`_italic_` and `[text](url)`. Single line breaks
+/// are kept, since that's what the author saw in the text box.
+fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut open_list: Option<&str> = None;
+    let mut in_paragraph = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+
+        let heading_level = trimmed.chars().take_while(|c| *c == '#').count();
+        let list_item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+            .map(|item| ("ul", item))
+            .or_else(|| {
+                let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+                (digits > 0)
+                    .then(|| trimmed[digits..].strip_prefix(". ").or_else(|| trimmed[digits..].strip_prefix(") ")))
+                    .flatten()
+                    .map(|item| ("ol", item))
+            });
+
+        if trimmed.is_empty() || (1..=6).contains(&heading_level) || list_item.is_some() {
+            if in_paragraph {
+                html.push_str("</p>");
+                in_paragraph = false;
+            }
+        }
+        if list_item.map(|(tag, _)| tag) != open_list {
+            if let Some(tag) = open_list.take() {
+                html.push_str(&format!("</{}>", tag));
+            }
+        }
+
+        if trimmed.is_empty() {
+            continue;
+        }
+        if (1..=6).contains(&heading_level) && trimmed[heading_level..].starts_with(' ') {
+            html.push_str(&format!(
+                "<h{0}>{1}</h{0}>",
+                heading_level,
+                inline_markdown(trimmed[heading_level..].trim())
+            ));
+        } else if let Some((tag, item)) = list_item {
+            if open_list.is_none() {
+                html.push_str(&format!("<{}>", tag));
+                open_list = Some(tag);
+            }
+            html.push_str(&format!("<li>{}</li>", inline_markdown(item)));
+        } else {
+            if in_paragraph {
+                html.push_str("<br>");
+            } else {
+                html.push_str("<p>");
+                in_paragraph = true;
+            }
+            html.push_str(&inline_markdown(trimmed));
+        }
+    }
+
+    if in_paragraph {
+        html.push_str("</p>");
+    }
+    if let Some(tag) = open_list {
+        html.push_str(&format!("</{}>", tag));
+    }
+    html
+}
+
+fn inline_markdown(text: &str) -> String {
+    let linked = replace_links(text);
+    let bold = replace_delimited(&replace_delimited(&linked, "**", "strong"), "__", "strong");
+    replace_delimited(&replace_delimited(&bold, "*", "em"), "_", "em")
+}
+
+/// Wraps text between pairs of `marker` in `<tag>`; an unpaired marker is left alone.
+fn replace_delimited(text: &str, marker: &str, tag: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(marker) {
+        let after = &rest[start + marker.len()..];
+        // `_` inside words (snake_case, file names) isn't emphasis
+        let inside_word = marker == "_"
+            && rest[..start].chars().last().map_or(false, char::is_alphanumeric);
+        match after.find(marker) {
+            Some(end) if end > 0 && !inside_word && !after.starts_with(' ') => {
+                result.push_str(&rest[..start]);
+                result.push_str(&format!("<{0}>{1}</{0}>", tag, &after[..end]));
+                rest = &after[end + marker.len()..];
+            }
+            _ => {
+                result.push_str(&rest[..start + marker.len()]);
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Turns `[text](url)` into links and escapes everything for HTML.
+fn replace_links(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else {
+            break;
+        };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else {
+            break;
+        };
+        result.push_str(&escape_html(&rest[..open]));
+        result.push_str(&format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(&rest[close + 2..end]),
+            escape_html(&rest[open + 1..close])
+        ));
+        rest = &rest[end + 1..];
+    }
+    result.push_str(&escape_html(rest));
+    result
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Mark {
+    #[default]
+    Plain,
+    Strong,
+    Emphasis,
+    Link,
+    Code,
+}
+
+/// Tells html2text to tag styles instead of drawing them with `*` and `_`.
+struct Decorator;
+
+impl TextDecorator for Decorator {
+    type Annotation = Mark;
+
+    fn decorate_link_start(&mut self, _url: &str) -> (String, Mark) {
+        (String::new(), Mark::Link)
+    }
+
+    fn decorate_link_end(&mut self) -> String {
+        String::new()
+    }
+
+    fn decorate_em_start(&self) -> (String, Mark) {
+        (String::new(), Mark::Emphasis)
+    }
+
+    fn decorate_em_end(&self) -> String {
+        String::new()
+    }
+
+    fn decorate_strong_start(&self) -> (String, Mark) {
+        (String::new(), Mark::Strong)
+    }
+
+    fn decorate_strong_end(&self) -> String {
+        String::new()
+    }
+
+    fn decorate_strikeout_start(&self) -> (String, Mark) {
+        (String::new(), Mark::Plain)
+    }
+
+    fn decorate_strikeout_end(&self) -> String {
+        String::new()
+    }
+
+    fn decorate_code_start(&self) -> (String, Mark) {
+        (String::new(), Mark::Code)
+    }
+
+    fn decorate_code_end(&self) -> String {
+        String::new()
+    }
+
+    fn decorate_preformat_first(&self) -> Mark {
+        Mark::Code
+    }
+
+    fn decorate_preformat_cont(&self) -> Mark {
+        Mark::Code
+    }
+
+    fn decorate_image(&mut self, _src: &str, title: &str) -> (String, Mark) {
+        (title.to_string(), Mark::Plain)
+    }
+
+    fn header_prefix(&self, _level: usize) -> String {
+        HEADING_MARKER.to_string()
+    }
+
+    fn quote_prefix(&self) -> String {
+        String::new()
+    }
+
+    fn unordered_item_prefix(&self) -> String {
+        String::from("• ")
+    }
+
+    fn ordered_item_prefix(&self, i: i64) -> String {
+        format!("{}. ", i)
+    }
+
+    fn make_subblock_decorator(&self) -> Self {
+        Decorator
+    }
+
+    fn finalise(&mut self, _links: Vec<String>) -> Vec<TaggedLine<Mark>> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(lines: &[Line]) -> Vec<LineKind> {
+        lines.iter().map(|line| line.kind).collect()
+    }
+
+    #[test]
+    fn markdown_blocks_become_html() {
+        assert_eq!(
+            markdown_to_html("# Potluck\nBring a dish\nand a friend\n\n- Salads\n- Desserts\n1. First"),
+            "<h1>Potluck</h1><p>Bring a dish<br>and a friend</p>\
+             <ul><li>Salads</li><li>Desserts</li></ul><ol><li>First</li></ol>"
+        );
+    }
+
+    #[test]
+    fn inline_markdown_is_styled_and_escaped() {
+        assert_eq!(inline_markdown("**Free** _lunch_ & *games*"), "<strong>Free</strong> <em>lunch</em> &amp; <em>games</em>");
+        assert_eq!(inline_markdown("file_name_here and 2 * 3"), "file_name_here and 2 * 3");
+        assert_eq!(
+            replace_links("Sign up [here](https://example.com/form) today"),
+            "Sign up <a href=\"https://example.com/form\">here</a> today"
+        );
+        assert_eq!(
+            replace_links("[Q&A <live>](https://example.com/?a=1&b=\"x\")"),
+            "<a href=\"https://example.com/?a=1&amp;b=&quot;x&quot;\">Q&amp;A &lt;live&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn parses_styles_and_line_kinds() {
+        let lines = parse("<h2>Youth night</h2><p>Bring <b>snacks</b> and <em>friends</em></p><ul><li>Games</li></ul>");
+
+        assert_eq!(kinds(&lines), [LineKind::Heading, LineKind::Blank, LineKind::Text, LineKind::Blank, LineKind::ListItem]);
+        assert_eq!(lines[0].text(), "Youth night");
+        let styled: Vec<_> = lines[2].spans.iter().map(|span| (span.text.as_str(), span.strong, span.emphasis)).collect();
+        assert_eq!(styled, [("Bring ", false, false), ("snacks", true, false), (" and ", false, false), ("friends", false, true)]);
+        assert_eq!(lines[4].text(), "• Games");
+    }
+
+    #[test]
+    fn bold_tags_with_attributes_or_capitals_are_strong() {
+        assert_eq!(
+            bold_to_strong("<B class=\"x\">One</B> <b>two</b> <br><body><b/>"),
+            "<strong class=\"x\">One</strong> <strong>two</strong> <br><body><strong/>"
+        );
+        let lines = parse("<p><B style=\"color: red\">Free</B> lunch</p>");
+        assert!(lines[0].spans[0].strong);
+        assert!(!lines[0].spans[1].strong);
+    }
+
+    #[test]
+    fn plain_text_keeps_line_breaks() {
+        let lines = parse("Doors open at 6\n\n\nDinner at 7");
+        assert_eq!(kinds(&lines), [LineKind::Text, LineKind::Blank, LineKind::Text]);
+        assert_eq!(plain_text(&lines), "Doors open at 6\nDinner at 7");
+    }
+}
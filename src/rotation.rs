@@ -0,0 +1,407 @@
+use crate::config::RotationMode;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// What a strategy needs to know about each slide in the rotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlideInfo {
+    pub id: String,
+    pub weight: u32,
+    pub is_featured: bool,
+    pub category: String,
+    /// Start time for events; other slides are treated as happening now
+    pub start: Option<DateTime<Utc>>,
+}
+
+/// Decides which slide comes after the one on screen.
+pub trait Strategy: Send + std::fmt::Debug {
+    /// Index into `slides` (in chronological order) of the next slide.
+    fn next(&mut self, slides: &[SlideInfo], current: Option<&str>) -> Option<usize>;
+}
+
+pub fn from_config(mode: RotationMode, half_life_days: f64) -> Box<dyn Strategy> {
+    match mode {
+        RotationMode::Chronological => Box::new(Cycle::new(Order::Chronological)),
+        RotationMode::FeaturedFirst => Box::new(Cycle::new(Order::FeaturedFirst)),
+        RotationMode::ByCategory => Box::new(Cycle::new(Order::ByCategory)),
+        RotationMode::WeightedRandom => Box::new(WeightedRandom),
+        RotationMode::SoonestFirst => Box::new(SoonestFirst::new(half_life_days)),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Order {
+    Chronological,
+    FeaturedFirst,
+    /// Categories in order of their first event, chronological within each
+    ByCategory,
+}
+
+impl Order {
+    fn arrange(self, slides: &[SlideInfo]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..slides.len()).collect();
+        match self {
+            Order::Chronological => {}
+            Order::FeaturedFirst => order.sort_by_key(|index| !slides[*index].is_featured),
+            Order::ByCategory => {
+                let mut first_seen: HashMap<&str, usize> = HashMap::new();
+                for (index, slide) in slides.iter().enumerate() {
+                    first_seen.entry(slide.category.as_str()).or_insert(index);
+                }
+                order.sort_by_key(|index| first_seen[slides[*index].category.as_str()]);
+            }
+        }
+        order
+    }
+}
+
+/// A fixed cycle in which each slide appears `weight` times, spread out with
+/// smooth weighted round-robin so a featured event isn't shown twice in a row
+/// (unless it takes up more than half of the cycle). Ties go to the earlier
+/// slide, so with equal weights the cycle follows the chosen order exactly.
+#[derive(Debug)]
+struct Cycle {
+    order: Order,
+    // The slides the cycle was built from, to notice when they change
+    built_from: Vec<SlideInfo>,
+    cycle: Vec<String>,
+    position: usize,
+}
+
+impl Cycle {
+    fn new(order: Order) -> Self {
+        Self {
+            order,
+            built_from: Vec::new(),
+            cycle: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn rebuild(&mut self, slides: &[SlideInfo], current: Option<&str>) {
+        let ordered: Vec<&SlideInfo> = self.order.arrange(slides).into_iter().map(|i| &slides[i]).collect();
+        let total: i64 = ordered.iter().map(|slide| slide.weight.max(1) as i64).sum();
+        // Starting each slide one step behind makes the first pick follow the order
+        // instead of going to the heaviest slide
+        let mut credit: Vec<i64> = ordered.iter().map(|slide| -(slide.weight.max(1) as i64)).collect();
+        let mut cycle = Vec::with_capacity(total as usize);
+
+        for _ in 0..total {
+            for (credit, slide) in credit.iter_mut().zip(&ordered) {
+                *credit += slide.weight.max(1) as i64;
+            }
+            // max_by_key keeps the last maximum, so search in reverse to favour earlier slides
+            let (pick, _) = credit
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, credit)| **credit)
+                .expect("slides is not empty");
+            credit[pick] -= total;
+            cycle.push(ordered[pick].id.clone());
+        }
+        spread_repeats(&mut cycle);
+
+        self.position = current
+            .and_then(|current| cycle.iter().position(|id| id == current))
+            .map_or(0, |index| index + 1);
+        tracing::info!("Rebuilt {:?} rotation: {} slides, {} per cycle", self.order, slides.len(), cycle.len());
+
+        self.cycle = cycle;
+        self.built_from = slides.to_vec();
+    }
+}
+
+impl Strategy for Cycle {
+    fn next(&mut self, slides: &[SlideInfo], current: Option<&str>) -> Option<usize> {
+        if slides.is_empty() {
+            return None;
+        }
+        if self.built_from != slides {
+            self.rebuild(slides, current);
+        }
+
+        let id = &self.cycle[self.position % self.cycle.len()];
+        self.position = (self.position + 1) % self.cycle.len();
+        slides.iter().position(|slide| &slide.id == id)
+    }
+}
+
+/// The cycle repeats, so a slide at both its end and its start would still
+/// play twice in a row. Swaps entries until no slide follows itself, as far
+/// as the weights allow.
+fn spread_repeats(cycle: &mut [String]) {
+    let len = cycle.len();
+    if len < 2 {
+        return;
+    }
+    let repeats = |cycle: &[String]| (0..len).filter(|i| cycle[*i] == cycle[(i + 1) % len]).count();
+
+    let mut remaining = repeats(cycle);
+    for i in 0..len {
+        if remaining == 0 {
+            return;
+        }
+        if cycle[i] != cycle[(i + 1) % len] {
+            continue;
+        }
+        // Move either half of the pair somewhere it doesn't cause a new repeat
+        'search: for moved in [(i + 1) % len, i] {
+            for j in 0..len {
+                cycle.swap(moved, j);
+                let after = repeats(cycle);
+                if after < remaining {
+                    remaining = after;
+                    break 'search;
+                }
+                cycle.swap(moved, j);
+            }
+        }
+    }
+}
+
+/// Plays the slides in the order given, as a playlist does. Keeps its own
+/// position, so a slide listed more than once doesn't jump back to its
+/// first copy.
+#[derive(Debug, Default)]
+pub struct InOrder {
+    position: Option<usize>,
+}
+
+impl Strategy for InOrder {
+    fn next(&mut self, slides: &[SlideInfo], current: Option<&str>) -> Option<usize> {
+        if slides.is_empty() {
+            return None;
+        }
+
+        let at = self
+            .position
+            .filter(|position| slides.get(*position).map(|slide| slide.id.as_str()) == current)
+            .or_else(|| current.and_then(|current| slides.iter().position(|slide| slide.id == current)));
+        let next = at.map_or(0, |position| (position + 1) % slides.len());
+        self.position = Some(next);
+        Some(next)
+    }
+}
+
+/// Picks at random in proportion to weight, never the slide already on screen.
+#[derive(Debug)]
+struct WeightedRandom;
+
+impl Strategy for WeightedRandom {
+    fn next(&mut self, slides: &[SlideInfo], current: Option<&str>) -> Option<usize> {
+        let candidates: Vec<usize> = (0..slides.len())
+            .filter(|index| slides.len() == 1 || Some(slides[*index].id.as_str()) != current)
+            .collect();
+        let total: u64 = candidates.iter().map(|index| slides[*index].weight.max(1) as u64).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = fastrand::u64(0..total);
+        for index in candidates {
+            let weight = slides[index].weight.max(1) as u64;
+            if roll < weight {
+                return Some(index);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+/// Gives events starting soon most of the screen time. A slide's weight
+/// halves for every `half_life_days` until it starts, so next month's events
+/// still come up, just less often.
+///
+/// Uses smooth weighted round-robin with credit kept per id, so the mix
+/// adapts as events approach without restarting the rotation.
+#[derive(Debug)]
+struct SoonestFirst {
+    half_life_days: f64,
+    credit: HashMap<String, f64>,
+}
+
+impl SoonestFirst {
+    fn new(half_life_days: f64) -> Self {
+        Self {
+            half_life_days: half_life_days.max(0.1),
+            credit: HashMap::new(),
+        }
+    }
+
+    fn effective_weight(&self, slide: &SlideInfo, now: DateTime<Utc>) -> f64 {
+        let days_until = slide.start.map_or(0, |start| (start - now).num_minutes().max(0)) as f64 / (24.0 * 60.0);
+        slide.weight.max(1) as f64 * 0.5f64.powf(days_until / self.half_life_days)
+    }
+}
+
+impl Strategy for SoonestFirst {
+    fn next(&mut self, slides: &[SlideInfo], current: Option<&str>) -> Option<usize> {
+        if slides.is_empty() {
+            return None;
+        }
+
+        let now = Utc::now();
+        let weights: Vec<f64> = slides.iter().map(|slide| self.effective_weight(slide, now)).collect();
+        let total: f64 = weights.iter().sum();
+
+        self.credit.retain(|id, _| slides.iter().any(|slide| &slide.id == id));
+        for (slide, weight) in slides.iter().zip(&weights) {
+            *self.credit.entry(slide.id.clone()).or_insert(0.0) += weight;
+        }
+
+        // Highest credit wins and ties go to the sooner event. The slide on
+        // screen only gets another turn if it's the only one.
+        let mut best: Option<usize> = None;
+        for (index, slide) in slides.iter().enumerate() {
+            if slides.len() > 1 && Some(slide.id.as_str()) == current {
+                continue;
+            }
+            if best.map_or(true, |b| self.credit[&slide.id] > self.credit[&slides[b].id]) {
+                best = Some(index);
+            }
+        }
+
+        let pick = best?;
+        if let Some(credit) = self.credit.get_mut(&slides[pick].id) {
+            *credit -= total;
+        }
+        Some(pick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn repeats(cycle: &[String]) -> usize {
+        (0..cycle.len()).filter(|i| cycle[*i] == cycle[(i + 1) % cycle.len()]).count()
+    }
+
+    fn sorted(mut cycle: Vec<String>) -> Vec<String> {
+        cycle.sort();
+        cycle
+    }
+
+    #[test]
+    fn separates_neighbours() {
+        let mut spread = cycle(&["a", "a", "b", "b", "c"]);
+        spread_repeats(&mut spread);
+        assert_eq!(repeats(&spread), 0);
+        assert_eq!(sorted(spread), cycle(&["a", "a", "b", "b", "c"]));
+    }
+
+    #[test]
+    fn separates_the_end_from_the_start() {
+        let mut spread = cycle(&["a", "b", "c", "a"]);
+        spread_repeats(&mut spread);
+        assert_eq!(repeats(&spread), 0);
+    }
+
+    #[test]
+    fn heavy_slide_repeats_as_little_as_possible() {
+        // Three of four slots can't all be kept apart
+        let mut spread = cycle(&["a", "a", "a", "b"]);
+        spread_repeats(&mut spread);
+        assert_eq!(repeats(&spread), 2);
+        assert_eq!(sorted(spread), cycle(&["a", "a", "a", "b"]));
+    }
+
+    fn slides(specs: &[(&str, u32, &str)]) -> Vec<SlideInfo> {
+        specs
+            .iter()
+            .enumerate()
+            .map(|(index, (id, weight, category))| SlideInfo {
+                id: id.to_string(),
+                weight: *weight,
+                is_featured: *weight > 1,
+                category: category.to_string(),
+                start: Some(Utc::now() + chrono::Duration::hours(index as i64 + 1)),
+            })
+            .collect()
+    }
+
+    fn play(strategy: &mut dyn Strategy, slides: &[SlideInfo], count: usize) -> Vec<String> {
+        let mut current: Option<String> = None;
+        (0..count)
+            .map(|_| {
+                let index = strategy.next(slides, current.as_deref()).unwrap();
+                current = Some(slides[index].id.clone());
+                slides[index].id.clone()
+            })
+            .collect()
+    }
+
+    fn in_a_row(shown: &[String]) -> usize {
+        shown.windows(2).filter(|pair| pair[0] == pair[1]).count()
+    }
+
+    #[test]
+    fn equal_weights_stay_chronological() {
+        let slides = slides(&[("a", 1, ""), ("b", 1, ""), ("c", 1, "")]);
+        let mut strategy = from_config(RotationMode::Chronological, 7.0);
+        assert_eq!(play(strategy.as_mut(), &slides, 6), cycle(&["a", "b", "c", "a", "b", "c"]));
+    }
+
+    #[test]
+    fn featured_slides_appear_weight_times_per_cycle() {
+        let slides = slides(&[("a", 1, ""), ("featured", 3, ""), ("c", 1, ""), ("d", 1, "")]);
+        let shown = play(from_config(RotationMode::Chronological, 7.0).as_mut(), &slides, 6);
+
+        assert_eq!(shown.iter().filter(|id| *id == "featured").count(), 3);
+        assert_eq!(repeats(&shown), 0);
+    }
+
+    #[test]
+    fn orders_rearrange_the_cycle() {
+        let mut slides = slides(&[("a", 1, "music"), ("b", 1, "youth"), ("c", 1, "music"), ("d", 1, "youth")]);
+        slides[3].is_featured = true;
+
+        let shown = play(from_config(RotationMode::FeaturedFirst, 7.0).as_mut(), &slides, 4);
+        assert_eq!(shown, cycle(&["d", "a", "b", "c"]));
+
+        let shown = play(from_config(RotationMode::ByCategory, 7.0).as_mut(), &slides, 4);
+        assert_eq!(shown, cycle(&["a", "c", "b", "d"]));
+    }
+
+    #[test]
+    fn changed_slides_continue_after_the_current_one() {
+        let mut strategy = from_config(RotationMode::Chronological, 7.0);
+        let before = slides(&[("a", 1, ""), ("b", 1, ""), ("c", 1, "")]);
+        play(strategy.as_mut(), &before, 2);
+
+        let after = slides(&[("a", 1, ""), ("b", 1, ""), ("new", 1, ""), ("c", 1, "")]);
+        let next = strategy.next(&after, Some("b")).unwrap();
+        assert_eq!(after[next].id, "new");
+        assert_eq!(strategy.next(&[], None), None);
+    }
+
+    #[test]
+    fn weighted_random_never_repeats_the_current_slide() {
+        let slides = slides(&[("a", 1, ""), ("b", 5, "")]);
+        let shown = play(from_config(RotationMode::WeightedRandom, 7.0).as_mut(), &slides, 20);
+        assert_eq!(in_a_row(&shown), 0);
+    }
+
+    #[test]
+    fn soonest_first_favours_upcoming_events() {
+        let mut slides = slides(&[("soon", 1, ""), ("next", 1, ""), ("later", 1, "")]);
+        slides[2].start = Some(Utc::now() + chrono::Duration::days(60));
+
+        let shown = play(from_config(RotationMode::SoonestFirst, 7.0).as_mut(), &slides, 20);
+        assert!(shown.iter().filter(|id| *id == "later").count() <= 1);
+        assert_eq!(in_a_row(&shown), 0);
+    }
+
+    #[test]
+    fn in_order_walks_repeated_slides() {
+        let slides = slides(&[("a", 1, ""), ("b", 1, ""), ("a", 1, ""), ("c", 1, "")]);
+        let shown = play(&mut InOrder::default(), &slides, 5);
+        assert_eq!(shown, cycle(&["a", "b", "a", "c", "a"]));
+    }
+}
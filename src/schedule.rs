@@ -0,0 +1,69 @@
+use crate::config::ScheduleRule;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+/// Index of the first rule in effect at `now`, which is wall-clock time in
+/// the configured zone.
+pub fn active_rule(rules: &[ScheduleRule], now: NaiveDateTime) -> Option<usize> {
+    rules.iter().position(|rule| covers(rule, now))
+}
+
+fn covers(rule: &ScheduleRule, now: NaiveDateTime) -> bool {
+    let on = |date: NaiveDate| rule.days.is_empty() || rule.days.contains(&date.weekday());
+    let time = now.time();
+
+    if rule.start == rule.end {
+        on(now.date())
+    } else if rule.start < rule.end {
+        on(now.date()) && time >= rule.start && time < rule.end
+    } else {
+        // Past midnight, e.g. 22:00-02:00: the early hours belong to the day before
+        (on(now.date()) && time >= rule.start) || (on(now.date() - Duration::days(1)) && time < rule.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Weekday};
+
+    fn rule(days: &[Weekday], start: &str, end: &str) -> ScheduleRule {
+        ScheduleRule {
+            name: String::new(),
+            days: days.to_vec(),
+            start: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+            end: NaiveTime::parse_from_str(end, "%H:%M").unwrap(),
+            playlist: None,
+            categories: Vec::new(),
+        }
+    }
+
+    // 2025-03-07 is a Friday
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn overnight_rule_runs_into_the_next_morning() {
+        let friday_night = rule(&[Weekday::Fri], "22:00", "02:00");
+        assert!(covers(&friday_night, at("2025-03-07 22:00")));
+        assert!(covers(&friday_night, at("2025-03-08 01:59")));
+        assert!(!covers(&friday_night, at("2025-03-08 02:00")));
+        // Saturday night isn't Friday's, nor are the early hours of Friday itself
+        assert!(!covers(&friday_night, at("2025-03-08 23:00")));
+        assert!(!covers(&friday_night, at("2025-03-07 01:00")));
+    }
+
+    #[test]
+    fn equal_times_cover_the_whole_day() {
+        let sunday = rule(&[Weekday::Sun], "00:00", "00:00");
+        assert!(covers(&sunday, at("2025-03-09 23:59")));
+        assert!(!covers(&sunday, at("2025-03-10 00:00")));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = [rule(&[Weekday::Fri], "09:00", "12:00"), rule(&[], "00:00", "00:00")];
+        assert_eq!(active_rule(&rules, at("2025-03-07 10:00")), Some(0));
+        assert_eq!(active_rule(&rules, at("2025-03-07 13:00")), Some(1));
+    }
+}
@@ -0,0 +1,488 @@
+use crate::config::{ImageFit, WidgetKind};
+use crate::{fit, richtext, rotation, timezone, Event, SETTINGS, TIMEZONE};
+use std::time::Duration;
+
+/// Anything the sign can put on screen. Events come from the sources; the
+/// other kinds are defined in `[[slides]]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slide {
+    Event(Event),
+    Announcement(Announcement),
+    Image(ImageSlide),
+    Welcome(Welcome),
+    Widget(Widget),
+    Agenda(Agenda),
+}
+
+/// Rotation settings shared by every slide that doesn't come from an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub id: String,
+    pub weight: u32,
+    /// Fixed time on screen per page; `None` means the usual duration for the kind
+    pub duration: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub timing: Timing,
+    pub title: String,
+    // Split to fit the screen like event descriptions, shown one page at a time
+    pub pages: Vec<Vec<richtext::Line>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageSlide {
+    pub timing: Timing,
+    pub url: String,
+    pub fit: ImageFit,
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Welcome {
+    pub timing: Timing,
+    pub title: String,
+    pub subtitle: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Widget {
+    pub timing: Timing,
+    pub kind: WidgetKind,
+}
+
+/// Upcoming events listed under their dates, several to a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Agenda {
+    pub timing: Timing,
+    pub title: String,
+    pub days: Option<u32>,
+    pub max_events: Option<usize>,
+    pub per_page: usize,
+    pub layout: AgendaLayout,
+    // Filled in from the current events by `Slide::with_events`
+    pub pages: Vec<Vec<AgendaDay>>,
+}
+
+/// Sizes in the agenda's list, in pixels, so pages are filled to what fits
+/// on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgendaLayout {
+    /// Room for the list below the title
+    pub height: f32,
+    pub heading: f32,
+    pub entry: f32,
+    /// Between every heading and entry
+    pub spacing: f32,
+    /// Where an entry's title and its time and location go
+    pub title: fit::Area,
+    pub details: fit::Area,
+}
+
+/// The events on one date, as far as they fit on the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgendaDay {
+    pub date: chrono::NaiveDate,
+    /// The date as event slides show it
+    pub heading: String,
+    pub entries: Vec<AgendaEntry>,
+}
+
+/// One event in an agenda, with its text fitted to a single line each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgendaEntry {
+    pub event: Event,
+    pub title: fit::Fitted,
+    pub details: fit::Fitted,
+}
+
+impl AgendaEntry {
+    fn new(event: &Event, layout: &AgendaLayout) -> Self {
+        let mut details = format!("{} - {}", event.start_time, event.end_time);
+        if !event.location.is_empty() {
+            details.push_str(" · ");
+            details.push_str(&event.location);
+        }
+
+        Self {
+            event: event.clone(),
+            title: fit::fit(&event.title, layout.title),
+            details: fit::fit(&details, layout.details),
+        }
+    }
+}
+
+impl Agenda {
+    /// Spreads the events in range over pages, as many as fit the screen and
+    /// at most `per_page` to a page. A date that continues on the next page
+    /// gets its heading again there.
+    fn paginate(&mut self, events: &[Event]) {
+        let days = match (self.days, self.max_events) {
+            (None, None) => Some(7),
+            (days, _) => days,
+        };
+        let today = timezone::today(*TIMEZONE);
+        let listed = events
+            .iter()
+            .filter(|event| {
+                days.map_or(true, |days| {
+                    let date = event.timestamp.with_timezone(&*TIMEZONE).date_naive();
+                    (date - today).num_days() < i64::from(days)
+                })
+            })
+            .take(self.max_events.unwrap_or(usize::MAX));
+
+        let per_page = self.per_page.max(1);
+        let layout = self.layout;
+        // Height of the last page's list so far, and how many entries it has
+        let (mut used, mut count) = (0.0, 0);
+        self.pages = Vec::new();
+        for event in listed {
+            let date = event.timestamp.with_timezone(&*TIMEZONE).date_naive();
+            let same_day = |page: &Vec<AgendaDay>| page.last().map_or(false, |day| day.date == date);
+            let needed = |page: &Vec<AgendaDay>| match (page.is_empty(), same_day(page)) {
+                (true, _) => layout.heading + layout.spacing + layout.entry,
+                (false, true) => layout.spacing + layout.entry,
+                (false, false) => layout.spacing + layout.heading + layout.spacing + layout.entry,
+            };
+
+            // A page always takes at least one entry, however tall
+            let full = self.pages.last().map_or(true, |page| count >= per_page || used + needed(page) > layout.height);
+            if full {
+                self.pages.push(Vec::new());
+                (used, count) = (0.0, 0);
+            }
+            let page = self.pages.last_mut().expect("a page was just added");
+            used += needed(page);
+            count += 1;
+            let entry = AgendaEntry::new(event, &layout);
+            match page.last_mut() {
+                Some(day) if day.date == date => day.entries.push(entry),
+                _ => page.push(AgendaDay {
+                    date,
+                    heading: event.date.clone(),
+                    entries: vec![entry],
+                }),
+            }
+        }
+    }
+}
+
+impl Slide {
+    pub fn id(&self) -> &str {
+        match self {
+            Slide::Event(event) => &event.id,
+            Slide::Announcement(announcement) => &announcement.timing.id,
+            Slide::Image(image) => &image.timing.id,
+            Slide::Welcome(welcome) => &welcome.timing.id,
+            Slide::Widget(widget) => &widget.timing.id,
+            Slide::Agenda(agenda) => &agenda.timing.id,
+        }
+    }
+
+    pub fn as_event(&self) -> Option<&Event> {
+        match self {
+            Slide::Event(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    fn timing(&self) -> Option<&Timing> {
+        match self {
+            Slide::Event(_) => None,
+            Slide::Announcement(announcement) => Some(&announcement.timing),
+            Slide::Image(image) => Some(&image.timing),
+            Slide::Welcome(welcome) => Some(&welcome.timing),
+            Slide::Widget(widget) => Some(&widget.timing),
+            Slide::Agenda(agenda) => Some(&agenda.timing),
+        }
+    }
+
+    /// The same slide with a fixed time on screen per page, as a playlist
+    /// entry may set. `None` leaves the slide's own duration.
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        if duration.is_none() {
+            return self;
+        }
+        match &mut self {
+            Slide::Event(event) => event.duration = duration,
+            Slide::Announcement(announcement) => announcement.timing.duration = duration,
+            Slide::Image(image) => image.timing.duration = duration,
+            Slide::Welcome(welcome) => welcome.timing.duration = duration,
+            Slide::Widget(widget) => widget.timing.duration = duration,
+            Slide::Agenda(agenda) => agenda.timing.duration = duration,
+        }
+        self
+    }
+
+    /// The same slide with whatever it lists taken from `events`, which are
+    /// in start order. Only agendas list events; `None` means an agenda with
+    /// nothing in range, which isn't worth showing.
+    pub fn with_events(mut self, events: &[Event]) -> Option<Self> {
+        if let Slide::Agenda(agenda) = &mut self {
+            agenda.paginate(events);
+            if agenda.pages.is_empty() {
+                return None;
+            }
+        }
+        Some(self)
+    }
+
+    /// Short name of the kind, used as the category when ordering the rotation.
+    fn kind(&self) -> &'static str {
+        match self {
+            Slide::Event(_) => "event",
+            Slide::Announcement(_) => "announcement",
+            Slide::Image(_) => "image",
+            Slide::Welcome(_) => "welcome",
+            Slide::Widget(_) => "widget",
+            Slide::Agenda(_) => "agenda",
+        }
+    }
+
+    pub fn slide_info(&self) -> rotation::SlideInfo {
+        if let Slide::Event(event) = self {
+            return event.slide_info();
+        }
+
+        rotation::SlideInfo {
+            id: self.id().to_string(),
+            weight: self.timing().map_or(1, |timing| timing.weight.max(1)),
+            is_featured: false,
+            category: self.kind().to_string(),
+            start: None,
+        }
+    }
+
+    /// Images to load before the slide is shown; an agenda has a thumbnail per event.
+    pub fn image_urls(&self) -> Vec<&str> {
+        match self {
+            Slide::Event(event) => event.image_url.as_deref().into_iter().collect(),
+            Slide::Image(image) => vec![image.url.as_str()],
+            Slide::Agenda(agenda) => agenda.pages
+                .iter()
+                .flatten()
+                .flat_map(|day| &day.entries)
+                .filter_map(|entry| entry.event.image_url.as_deref())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn page_count(&self) -> usize {
+        let pages = match self {
+            Slide::Event(event) => event.description_pages.len(),
+            Slide::Announcement(announcement) => announcement.pages.len(),
+            Slide::Agenda(agenda) => agenda.pages.len(),
+            _ => 1,
+        };
+        pages.max(1)
+    }
+
+    /// How long `page` stays on screen. Announcements are timed like events,
+    /// by reading time if that is on; the other kinds by `slide_interval_seconds`.
+    pub fn page_duration(&self, page: usize) -> Duration {
+        match self {
+            Slide::Event(event) => event.page_duration(page),
+            Slide::Announcement(announcement) => announcement.timing.duration.unwrap_or_else(|| {
+                let page_text = announcement.pages
+                    .get(page)
+                    .map(|lines| richtext::plain_text(lines))
+                    .unwrap_or_default();
+                let words = announcement.title.split_whitespace().count() + page_text.split_whitespace().count();
+                SETTINGS.slide_duration(&announcement.timing.id, &announcement.title, "", false, words)
+            }),
+            other => other
+                .timing()
+                .and_then(|timing| timing.duration)
+                .unwrap_or_else(|| SETTINGS.slide_interval()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pocketbase::ApiEvent;
+
+    fn timing(id: &str, weight: u32, seconds: Option<u64>) -> Timing {
+        Timing { id: id.to_string(), weight, duration: seconds.map(Duration::from_secs) }
+    }
+
+    fn announcement(pages: usize) -> Slide {
+        Slide::Announcement(Announcement {
+            timing: timing("notice", 2, Some(12)),
+            title: String::from("Notice"),
+            pages: vec![richtext::parse("Parking lot closed"); pages],
+        })
+    }
+
+    #[test]
+    fn configured_slides_rotate_by_kind() {
+        let info = announcement(1).slide_info();
+        assert_eq!(info.id, "notice");
+        assert_eq!(info.weight, 2);
+        assert_eq!(info.category, "announcement");
+        assert_eq!(info.start, None);
+
+        let widget = Slide::Widget(Widget { timing: timing("clock", 0, None), kind: WidgetKind::Clock });
+        assert_eq!(widget.slide_info().weight, 1);
+    }
+
+    #[test]
+    fn only_announcements_and_events_have_pages() {
+        assert_eq!(announcement(3).page_count(), 3);
+        assert_eq!(announcement(0).page_count(), 1);
+
+        let welcome = Slide::Welcome(Welcome {
+            timing: timing("welcome", 1, None),
+            title: String::from("Welcome"),
+            subtitle: String::new(),
+        });
+        assert_eq!(welcome.page_count(), 1);
+        assert!(welcome.image_urls().is_empty());
+    }
+
+    #[test]
+    fn fixed_durations_apply_to_every_page() {
+        let slide = announcement(2);
+        assert_eq!(slide.page_duration(0), Duration::from_secs(12));
+        assert_eq!(slide.page_duration(1), Duration::from_secs(12));
+
+        let image = Slide::Image(ImageSlide {
+            timing: timing("poster", 1, Some(20)),
+            url: String::from("slides/poster.png"),
+            fit: ImageFit::Cover,
+            caption: None,
+        });
+        assert_eq!(image.page_duration(0), Duration::from_secs(20));
+        assert_eq!(image.image_urls(), ["slides/poster.png"]);
+        assert_eq!(image.as_event(), None);
+    }
+
+    fn event(id: &str, days_ahead: i64, hour: u32) -> Event {
+        let day = timezone::today(*TIMEZONE) + chrono::Duration::days(days_ahead);
+        let start = timezone::local_to_utc(day.and_hms_opt(hour, 0, 0).unwrap(), *TIMEZONE);
+        Event::from(ApiEvent {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: Some(format!("http://img/{}.png", id)),
+            thumbnail: None,
+            category: String::new(),
+            is_featured: false,
+            recurring_type: None,
+            created_at: start,
+            updated_at: start,
+        })
+    }
+
+    // Room for a heading and two entries, or two headings and one entry each
+    fn layout(height: f32) -> AgendaLayout {
+        let area = fit::Area { width: 1000.0, max_lines: 1, min_size: 20.0, max_size: 40.0, font: iced::Font::DEFAULT };
+        AgendaLayout { height, heading: 40.0, entry: 100.0, spacing: 20.0, title: area, details: area }
+    }
+
+    fn agenda(days: Option<u32>, max_events: Option<usize>, per_page: usize) -> Slide {
+        Slide::Agenda(Agenda {
+            timing: timing("week", 1, None),
+            title: String::from("This week"),
+            days,
+            max_events,
+            per_page,
+            layout: layout(10_000.0),
+            pages: Vec::new(),
+        })
+    }
+
+    fn listed(slide: &Slide) -> Vec<Vec<(String, Vec<&str>)>> {
+        let Slide::Agenda(agenda) = slide else {
+            panic!("not an agenda");
+        };
+        agenda.pages
+            .iter()
+            .map(|page| {
+                page.iter()
+                    .map(|day| (day.heading.clone(), day.entries.iter().map(|entry| entry.event.id.as_str()).collect()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn agenda_groups_events_by_date_across_pages() {
+        let events = [event("a", 1, 9), event("b", 1, 18), event("c", 1, 20), event("d", 2, 10)];
+        let (day1, day2) = (events[0].date.clone(), events[3].date.clone());
+
+        let slide = agenda(None, None, 2).with_events(&events).unwrap();
+        assert_eq!(
+            listed(&slide),
+            [vec![(day1.clone(), vec!["a", "b"])], vec![(day1, vec!["c"]), (day2, vec!["d"])]]
+        );
+        assert_eq!(slide.page_count(), 2);
+        assert_eq!(slide.image_urls().len(), 4);
+    }
+
+    #[test]
+    fn agenda_only_lists_events_in_range() {
+        let events = [event("a", 0, 23), event("b", 3, 10), event("c", 10, 10)];
+
+        let week = agenda(None, None, 10).with_events(&events).unwrap();
+        assert_eq!(listed(&week)[0].iter().flat_map(|(_, ids)| ids.clone()).collect::<Vec<_>>(), ["a", "b"]);
+
+        let first = agenda(None, Some(1), 10).with_events(&events).unwrap();
+        assert_eq!(listed(&first)[0].iter().flat_map(|(_, ids)| ids.clone()).collect::<Vec<_>>(), ["a"]);
+
+        assert_eq!(agenda(Some(7), None, 10).with_events(&events[2..]), None);
+    }
+
+    #[test]
+    fn agenda_pages_fill_to_height_counting_headings() {
+        let events = [event("a", 1, 9), event("b", 2, 9), event("c", 2, 12), event("d", 2, 15)];
+        let (day1, day2) = (events[0].date.clone(), events[1].date.clone());
+
+        let Slide::Agenda(mut agenda) = agenda(None, None, 10) else {
+            unreachable!();
+        };
+        agenda.layout = layout(340.0);
+        agenda.paginate(&events);
+        assert_eq!(
+            listed(&Slide::Agenda(agenda)),
+            [vec![(day1, vec!["a"]), (day2.clone(), vec!["b"])], vec![(day2, vec!["c", "d"])]]
+        );
+    }
+
+    #[test]
+    fn oversized_agenda_entry_still_gets_a_page() {
+        let events = [event("a", 1, 9), event("b", 1, 12)];
+        let day = events[0].date.clone();
+
+        let Slide::Agenda(mut agenda) = agenda(None, None, 10) else {
+            unreachable!();
+        };
+        agenda.layout = layout(50.0);
+        agenda.paginate(&events);
+        assert_eq!(listed(&Slide::Agenda(agenda)), [vec![(day.clone(), vec!["a"])], vec![(day, vec!["b"])]]);
+    }
+
+    #[test]
+    fn agenda_days_are_grouped_by_date_not_by_heading() {
+        let mut events = [event("a", 1, 9), event("b", 2, 9)];
+        // A date format without the day of the month gives two dates one heading
+        for event in &mut events {
+            event.date = String::from("Sunday");
+        }
+
+        let slide = agenda(None, None, 10).with_events(&events).unwrap();
+        assert_eq!(
+            listed(&slide),
+            [vec![(String::from("Sunday"), vec!["a"]), (String::from("Sunday"), vec!["b"])]]
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use crate::auth::Authenticator;
+use crate::config::{ApiMode, SourceConfig};
+use crate::health::RetryAfter;
+use crate::icalendar::{CalendarLocation, IcalSource};
+use crate::local::LocalSource;
+use crate::pocketbase::{ApiClient, ApiEvent};
+use crate::recurrence;
+use anyhow::Result;
+use chrono_tz::Tz;
+use futures::future::BoxFuture;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Anything that can produce a list of upcoming events.
+pub trait EventSource: Send + Sync + std::fmt::Debug {
+    fn fetch_events(&self) -> BoxFuture<'_, Result<Vec<ApiEvent>>>;
+}
+
+impl EventSource for ApiClient {
+    fn fetch_events(&self) -> BoxFuture<'_, Result<Vec<ApiEvent>>> {
+        Box::pin(ApiClient::fetch_events(self))
+    }
+}
+
+pub fn from_config(config: &SourceConfig, timezone: Tz) -> Result<Box<dyn EventSource>> {
+    let source: Box<dyn EventSource> = match config {
+        SourceConfig::Pocketbase(source) => {
+            let mut client = ApiClient::new(source.api_url.clone());
+            if let Some(auth) = &source.auth {
+                client = client.with_auth(Arc::new(Authenticator::new(auth.clone(), source.api_url.clone())));
+            }
+            match source.api_mode {
+                ApiMode::Upcoming => Box::new(client),
+                ApiMode::Collection => Box::new(client.with_collection(source.collection_query())),
+            }
+        }
+        SourceConfig::Ical(source) => {
+            let location = match (&source.url, &source.path) {
+                (Some(url), None) => CalendarLocation::Url(url.clone()),
+                (None, Some(path)) => CalendarLocation::Path(path.clone()),
+                _ => return Err(anyhow::anyhow!(
+                    "Calendar source '{}' needs exactly one of `url` or `path`",
+                    source.name
+                )),
+            };
+            Box::new(IcalSource::new(location, source.horizon_days, source.category.clone(), timezone))
+        }
+        SourceConfig::Local(source) => Box::new(LocalSource::new(source.path.clone())),
+    };
+    Ok(source)
+}
+
+#[derive(Debug)]
+struct RegisteredSource {
+    name: String,
+    source: Box<dyn EventSource>,
+    // Reused when this source fails so one flaky calendar doesn't empty its share of the rotation
+    last_good: Mutex<Option<Vec<ApiEvent>>>,
+}
+
+/// One round of fetching from every source.
+#[derive(Debug)]
+pub struct MergedEvents {
+    pub events: Vec<ApiEvent>,
+    /// `name: error` for every source that failed this time. Their earlier
+    /// events, if any, are still in `events`.
+    pub failures: Vec<String>,
+    /// The longest wait any failed source asked for
+    pub retry_after: Option<RetryAfter>,
+}
+
+impl MergedEvents {
+    /// Whether every source answered, so `events` is all fresh data.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// All configured sources, fetched together and merged into one sorted list.
+#[derive(Debug)]
+pub struct MergedSource {
+    sources: Vec<RegisteredSource>,
+    timezone: Tz,
+    recurring_horizon_days: u32,
+}
+
+impl MergedSource {
+    pub fn from_configs(configs: &[SourceConfig], timezone: Tz, recurring_horizon_days: u32) -> Self {
+        let sources = configs
+            .iter()
+            .filter_map(|config| match from_config(config, timezone) {
+                Ok(source) => {
+                    tracing::info!("Registering event source '{}'", config.name());
+                    Some(RegisteredSource {
+                        name: config.name().to_string(),
+                        source,
+                        last_good: Mutex::new(None),
+                    })
+                }
+                Err(e) => {
+                    tracing::error!("Skipping event source '{}': {}", config.name(), e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { sources, timezone, recurring_horizon_days }
+    }
+
+    /// Fails only when every source failed; otherwise sources that failed
+    /// fill in with their last good events and are listed in `failures`.
+    pub async fn fetch_events(&self) -> Result<MergedEvents> {
+        let results = futures::future::join_all(
+            self.sources.iter().map(|registered| registered.source.fetch_events())
+        ).await;
+
+        let mut merged = Vec::new();
+        let mut failures = Vec::new();
+        let mut retry_after: Option<RetryAfter> = None;
+
+        for (registered, result) in self.sources.iter().zip(results) {
+            let mut last_good = registered.last_good.lock().unwrap();
+            match result {
+                Ok(events) => {
+                    tracing::info!("Source '{}' returned {} events", registered.name, events.len());
+                    *last_good = Some(events.clone());
+                    merged.extend(self.expand(&registered.name, events));
+                }
+                Err(e) => {
+                    tracing::error!("Source '{}' failed: {}", registered.name, e);
+                    retry_after = retry_after.max(RetryAfter::from_error(&e));
+                    if let Some(events) = last_good.as_ref() {
+                        tracing::info!("Reusing {} earlier events from '{}'", events.len(), registered.name);
+                        merged.extend(self.expand(&registered.name, events.clone()));
+                    }
+                    failures.push(format!("{}: {}", registered.name, e));
+                }
+            }
+        }
+
+        if failures.len() == self.sources.len() {
+            let message = format!("All event sources failed: {}", failures.join("; "));
+            return Err(match retry_after {
+                Some(retry_after) => anyhow::Error::new(retry_after).context(message),
+                None => anyhow::anyhow!(message),
+            });
+        }
+
+        let mut events = dedup_events(merged);
+        events.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        Ok(MergedEvents { events, failures, retry_after })
+    }
+
+    /// Expands a source's recurring events, tagging every event with the
+    /// source's name. Expanding comes before deduplicating, in case a server
+    /// also sends some instances itself.
+    fn expand<'a>(&self, source: &'a str, events: Vec<ApiEvent>) -> impl Iterator<Item = (&'a str, ApiEvent)> {
+        recurrence::expand_events(events, self.recurring_horizon_days, self.timezone)
+            .into_iter()
+            .map(move |event| (source, event))
+    }
+}
+
+/// Drops repeated events, keeping the copy from the earliest-declared source.
+///
+/// Ids are only unique within a source, so they're compared per source. The
+/// same event often shows up in more than one calendar with a different id,
+/// so title and start time are compared across sources.
+fn dedup_events(events: Vec<(&str, ApiEvent)>) -> Vec<ApiEvent> {
+    let mut seen_ids = HashSet::new();
+    let mut seen_keys = HashSet::new();
+
+    events
+        .into_iter()
+        .filter(|(source, event)| {
+            let title = event.title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            let id = (*source, event.id.clone());
+            let key = (title, event.start_time);
+            let is_new = !seen_ids.contains(&id) && !seen_keys.contains(&key);
+            if is_new {
+                seen_ids.insert(id);
+                seen_keys.insert(key);
+            } else {
+                tracing::info!("Dropping duplicate event '{}' ({})", event.title, event.id);
+            }
+            is_new
+        })
+        .map(|(_, event)| event)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::executor::block_on;
+
+    /// Answers each fetch with the next result from its script.
+    #[derive(Debug)]
+    struct Scripted(Mutex<Vec<Result<Vec<ApiEvent>>>>);
+
+    impl EventSource for Scripted {
+        fn fetch_events(&self) -> BoxFuture<'_, Result<Vec<ApiEvent>>> {
+            let next = self.0.lock().unwrap().remove(0);
+            Box::pin(futures::future::ready(next))
+        }
+    }
+
+    fn event(id: &str, hour: u32) -> ApiEvent {
+        let start = Utc.with_ymd_and_hms(2030, 1, 1, hour, 0, 0).unwrap();
+        ApiEvent {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            start_time: start,
+            end_time: start + Duration::hours(1),
+            location: String::new(),
+            location_url: None,
+            registration_url: None,
+            image: None,
+            thumbnail: None,
+            category: String::new(),
+            is_featured: false,
+            recurring_type: None,
+            created_at: start,
+            updated_at: start,
+        }
+    }
+
+    fn unavailable(secs: u64) -> anyhow::Error {
+        anyhow::Error::new(RetryAfter(std::time::Duration::from_secs(secs))).context("503 Service Unavailable")
+    }
+
+    fn merged(scripts: Vec<Vec<Result<Vec<ApiEvent>>>>) -> MergedSource {
+        let sources = scripts
+            .into_iter()
+            .enumerate()
+            .map(|(index, script)| RegisteredSource {
+                name: format!("source{}", index + 1),
+                source: Box::new(Scripted(Mutex::new(script))),
+                last_good: Mutex::new(None),
+            })
+            .collect();
+        MergedSource { sources, timezone: chrono_tz::UTC, recurring_horizon_days: 30 }
+    }
+
+    fn ids(merged: &MergedEvents) -> Vec<&str> {
+        merged.events.iter().map(|event| event.id.as_str()).collect()
+    }
+
+    #[test]
+    fn same_event_in_two_calendars_is_listed_once() {
+        let mut copy = event("other-id", 9);
+        copy.title = String::from("  A   ");
+        let events = dedup_events(vec![("one", event("a", 9)), ("two", copy), ("one", event("a", 10)), ("one", event("b", 9))]);
+        let ids: Vec<_> = events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[test]
+    fn same_id_from_another_source_is_a_different_event() {
+        let source = merged(vec![
+            vec![Ok(vec![event("1", 9)])],
+            vec![Ok(vec![ApiEvent { title: String::from("Other"), ..event("1", 10) }])],
+        ]);
+
+        let result = block_on(source.fetch_events()).unwrap();
+        let titles: Vec<_> = result.events.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, ["1", "Other"]);
+    }
+
+    #[test]
+    fn complete_when_every_source_answers() {
+        let source = merged(vec![
+            vec![Ok(vec![event("b", 10)])],
+            vec![Ok(vec![event("a", 9)])],
+        ]);
+
+        let result = block_on(source.fetch_events()).unwrap();
+        assert!(result.is_complete());
+        assert_eq!(ids(&result), ["a", "b"]);
+        assert_eq!(result.retry_after, None);
+    }
+
+    #[test]
+    fn failed_source_reuses_last_good_events_but_is_reported() {
+        let source = merged(vec![
+            vec![Ok(vec![event("a", 9)]), Ok(vec![event("a", 9)])],
+            vec![Ok(vec![event("b", 10)]), Err(unavailable(30))],
+        ]);
+        block_on(source.fetch_events()).unwrap();
+
+        let result = block_on(source.fetch_events()).unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(ids(&result), ["a", "b"]);
+        assert_eq!(result.failures.len(), 1);
+        assert!(result.failures[0].starts_with("source2: "));
+        assert_eq!(result.retry_after, Some(RetryAfter(std::time::Duration::from_secs(30))));
+    }
+
+    #[test]
+    fn failed_source_without_earlier_events_is_reported() {
+        let source = merged(vec![
+            vec![Ok(vec![event("a", 9)])],
+            vec![Err(anyhow::anyhow!("connection refused"))],
+        ]);
+
+        let result = block_on(source.fetch_events()).unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(ids(&result), ["a"]);
+    }
+
+    #[test]
+    fn every_source_failing_is_an_error_even_with_last_good_events() {
+        let source = merged(vec![
+            vec![Ok(vec![event("a", 9)]), Err(unavailable(10))],
+            vec![Ok(vec![event("b", 10)]), Err(unavailable(60))],
+        ]);
+        block_on(source.fetch_events()).unwrap();
+
+        let error = block_on(source.fetch_events()).unwrap_err();
+        assert_eq!(RetryAfter::from_error(&error), Some(RetryAfter(std::time::Duration::from_secs(60))));
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Places a wall-clock time in `tz` on the timeline.
+///
+/// Ambiguous times (when clocks fall back) resolve to the first occurrence;
+/// times skipped when clocks spring forward are moved past the gap.
+pub fn local_to_utc(local: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    if let Some(time) = tz.from_local_datetime(&local).earliest() {
+        return time.with_timezone(&Utc);
+    }
+
+    tz.from_local_datetime(&(local + Duration::hours(1)))
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+}
+
+/// Wall-clock time in `tz` for an instant.
+pub fn utc_to_local(time: DateTime<Utc>, tz: Tz) -> NaiveDateTime {
+    time.with_timezone(&tz).naive_local()
+}
+
+/// The current date in `tz`.
+pub fn today(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    fn naive(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn wall_clock_round_trips() {
+        let utc = local_to_utc(naive("2025-07-04 18:00"), New_York);
+        assert_eq!(utc, naive("2025-07-04 22:00").and_utc());
+        assert_eq!(utc_to_local(utc, New_York), naive("2025-07-04 18:00"));
+    }
+
+    #[test]
+    fn skipped_times_move_past_the_gap() {
+        // 2:30 doesn't exist on the morning clocks spring forward
+        assert_eq!(local_to_utc(naive("2025-03-09 02:30"), New_York), naive("2025-03-09 07:30").and_utc());
+    }
+
+    #[test]
+    fn repeated_times_use_the_first_occurrence() {
+        // 1:30 happens twice when clocks fall back, first in daylight time
+        assert_eq!(local_to_utc(naive("2025-11-02 01:30"), New_York), naive("2025-11-02 05:30").and_utc());
+    }
+}
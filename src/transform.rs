@@ -0,0 +1,81 @@
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer;
+use iced::advanced::widget::{Tree, Widget};
+use iced::{mouse, Element, Length, Rectangle, Size, Transformation, Vector};
+
+/// Draws its content shifted sideways and scaled around its centre. Layout
+/// is left alone, so the content is laid out as if it weren't moved.
+pub struct Transform<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    /// Fraction of the content's own width
+    offset: f32,
+    scale: f32,
+}
+
+impl<'a, Message, Theme, Renderer> Transform<'a, Message, Theme, Renderer> {
+    pub fn new(content: impl Into<Element<'a, Message, Theme, Renderer>>, offset: f32, scale: f32) -> Self {
+        Self {
+            content: content.into(),
+            offset,
+            scale,
+        }
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Transform<'a, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn layout(&self, tree: &mut Tree, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let center = Vector::new(bounds.center_x(), bounds.center_y());
+        let transformation = Transformation::translate(center.x + self.offset * bounds.width, center.y)
+            * Transformation::scale(self.scale)
+            * Transformation::translate(-center.x, -center.y);
+
+        // Anything pushed past the edges is clipped to where the content would normally be
+        renderer.with_layer(bounds, |renderer| {
+            renderer.with_transformation(transformation, |renderer| {
+                self.content
+                    .as_widget()
+                    .draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+            });
+        });
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Transform<'a, Message, Theme, Renderer>> for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: renderer::Renderer + 'a,
+{
+    fn from(transform: Transform<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(transform)
+    }
+}
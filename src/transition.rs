@@ -0,0 +1,141 @@
+use crate::config::{Easing, TransitionStyle, Transitions};
+use std::time::{Duration, Instant};
+
+/// How one of the two slides is drawn at some point during a transition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layer {
+    pub alpha: f32,
+    /// Horizontal shift as a fraction of the screen width, positive to the right
+    pub offset: f32,
+    pub scale: f32,
+}
+
+impl Layer {
+    const SHOWN: Layer = Layer { alpha: 1.0, offset: 0.0, scale: 1.0 };
+    const HIDDEN: Layer = Layer { alpha: 0.0, offset: 0.0, scale: 1.0 };
+
+    /// Whether the layer can be drawn as is, without the transform widget.
+    pub fn is_untransformed(&self) -> bool {
+        self.offset == 0.0 && self.scale == 1.0
+    }
+}
+
+/// A switch from the slide in `from` to whatever is on screen now.
+#[derive(Debug)]
+pub struct Transition<T> {
+    pub from: T,
+    style: TransitionStyle,
+    easing: Easing,
+    duration: Duration,
+    started: Instant,
+}
+
+impl<T> Transition<T> {
+    /// `None` when transitions are turned off, in which case the switch is instant.
+    pub fn start(from: T, settings: &Transitions) -> Option<Self> {
+        if settings.reduced_motion || settings.style == TransitionStyle::None || settings.duration_ms == 0 {
+            return None;
+        }
+
+        Some(Self {
+            from,
+            style: settings.style,
+            easing: settings.easing,
+            duration: Duration::from_millis(settings.duration_ms),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    /// The outgoing and incoming slide as they should be drawn right now.
+    pub fn layers(&self) -> (Layer, Layer) {
+        let linear = (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0);
+        layers(self.style, ease(self.easing, linear))
+    }
+}
+
+fn layers(style: TransitionStyle, progress: f32) -> (Layer, Layer) {
+    match style {
+        TransitionStyle::None => (Layer::HIDDEN, Layer::SHOWN),
+        TransitionStyle::Crossfade => (
+            Layer { alpha: 1.0 - progress, ..Layer::SHOWN },
+            Layer { alpha: progress, ..Layer::SHOWN },
+        ),
+        TransitionStyle::Slide => (
+            Layer { offset: -progress, ..Layer::SHOWN },
+            Layer { offset: 1.0 - progress, ..Layer::SHOWN },
+        ),
+        TransitionStyle::Zoom => (
+            Layer { alpha: 1.0 - progress, offset: 0.0, scale: 1.0 + 0.1 * progress },
+            Layer { alpha: progress, offset: 0.0, scale: 0.9 + 0.1 * progress },
+        ),
+        // Out through the background colour in the first half, in during the second
+        TransitionStyle::Dissolve if progress < 0.5 => (
+            Layer { alpha: 1.0 - 2.0 * progress, ..Layer::SHOWN },
+            Layer::HIDDEN,
+        ),
+        TransitionStyle::Dissolve => (
+            Layer::HIDDEN,
+            Layer { alpha: 2.0 * progress - 1.0, ..Layer::SHOWN },
+        ),
+    }
+}
+
+/// Cubic easing curves; `t` and the result run from 0 to 1.
+fn ease(easing: Easing, t: f32) -> f32 {
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseIn => t * t * t,
+        Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+        Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+        Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES: [TransitionStyle; 4] =
+        [TransitionStyle::Crossfade, TransitionStyle::Slide, TransitionStyle::Zoom, TransitionStyle::Dissolve];
+
+    #[test]
+    fn every_style_ends_on_the_new_slide() {
+        for style in STYLES {
+            let (_, start) = layers(style, 0.0);
+            assert!(start.alpha == 0.0 || start.offset == 1.0, "{:?} starts with the new slide shown", style);
+
+            let (old, new) = layers(style, 1.0);
+            assert_eq!(new, Layer::SHOWN, "{:?}", style);
+            assert!(old.alpha == 0.0 || old.offset == -1.0, "{:?} still shows the old slide", style);
+        }
+    }
+
+    #[test]
+    fn dissolve_passes_through_the_background() {
+        let (old, new) = layers(TransitionStyle::Dissolve, 0.5);
+        assert_eq!((old.alpha, new.alpha), (0.0, 0.0));
+        assert_eq!(layers(TransitionStyle::Dissolve, 0.25).0.alpha, 0.5);
+    }
+
+    #[test]
+    fn easing_curves_run_from_zero_to_one() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(ease(easing, 0.0), 0.0, "{:?}", easing);
+            assert_eq!(ease(easing, 1.0), 1.0, "{:?}", easing);
+        }
+        assert_eq!(ease(Easing::EaseInOut, 0.5), 0.5);
+        assert!(ease(Easing::EaseIn, 0.5) < 0.5 && ease(Easing::EaseOut, 0.5) > 0.5);
+    }
+
+    #[test]
+    fn reduced_motion_switches_instantly() {
+        let reduced = Transitions { reduced_motion: true, ..Transitions::default() };
+        assert!(Transition::start((), &reduced).is_none());
+        assert!(Transition::start((), &Transitions { duration_ms: 0, ..Transitions::default() }).is_none());
+        assert!(Transition::start((), &Transitions::default()).is_some());
+    }
+}